@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use arbitrary::{Arbitrary, Unstructured};
 use indradb::util::generate_temporary_path;
-use indradb::{Datastore, MemoryDatastore, RocksdbDatastore, Transaction};
+use indradb::{Datastore, MemoryDatastore, RocksdbDatastore, SledDatastore, Transaction};
 use libfuzzer_sys::fuzz_target;
 
 #[derive(Arbitrary, Clone, Debug, PartialEq)]
@@ -499,7 +499,7 @@ impl Into<f64> for FiniteFloat {
 
 macro_rules! cmp {
     ($v1:expr, $v2:expr) => {
-        match ($v1, $v2) {
+        match (&$v1, &$v2) {
             (Ok(v1), Ok(v2)) => {
                 assert_eq!(v1, v2);
             }
@@ -508,64 +508,119 @@ macro_rules! cmp {
             }
         }
     };
+    ($v1:expr, $v2:expr, $v3:expr) => {{
+        let (v1, v2, v3) = ($v1, $v2, $v3);
+        cmp!(v1, v2);
+        cmp!(v1, v3);
+    }};
+}
+
+// Checks invariants that should hold for any datastore after any sequence of
+// operations. Bulk inserts are allowed to skip validation, so dangling edges
+// are only checked for if there haven't been any.
+fn check_invariants<T: Transaction>(trans: &T, allow_dangling_edges: bool) {
+    let vertices = trans.get_vertices(indradb::RangeVertexQuery::new()).unwrap();
+    assert_eq!(trans.get_vertex_count().unwrap(), vertices.len() as u64);
+
+    let mut vertex_ids: Vec<uuid::Uuid> = vertices.iter().map(|v| v.id).collect();
+    let mut sorted_vertex_ids = vertex_ids.clone();
+    sorted_vertex_ids.sort();
+    assert_eq!(vertex_ids, sorted_vertex_ids);
+    vertex_ids.dedup();
+    assert_eq!(vertex_ids.len(), vertices.len());
+
+    for vertex in &vertices {
+        for direction in &[indradb::EdgeDirection::Outbound, indradb::EdgeDirection::Inbound] {
+            let q = indradb::PipeEdgeQuery::new(
+                Box::new(indradb::SpecificVertexQuery::single(vertex.id).into()),
+                *direction,
+            );
+            let edges = trans.get_edges(q).unwrap();
+            let count = trans.get_edge_count(vertex.id, None, *direction).unwrap();
+            assert_eq!(count, edges.len() as u64);
+
+            if !allow_dangling_edges {
+                for edge in &edges {
+                    let other_id = match direction {
+                        indradb::EdgeDirection::Outbound => edge.key.inbound_id,
+                        indradb::EdgeDirection::Inbound => edge.key.outbound_id,
+                    };
+                    assert!(vertex_ids.binary_search(&other_id).is_ok());
+                }
+            }
+        }
+    }
 }
 
 fuzz_target!(|ops: Vec<Op>| {
     let d1 = MemoryDatastore::default();
     let rocksdb_path = generate_temporary_path();
     let d2 = RocksdbDatastore::new(&rocksdb_path, Some(1)).unwrap();
+    let sled_path = generate_temporary_path();
+    let d3 = SledDatastore::new(&sled_path).unwrap();
 
     let t1 = d1.transaction().unwrap();
     let t2 = d2.transaction().unwrap();
+    let t3 = d3.transaction().unwrap();
+    let mut allow_dangling_edges = false;
 
     for op in ops {
         match op {
             Op::BulkInsert(items) => {
                 let items: Vec<indradb::BulkInsertItem> = items.into_iter().map(|i| i.into()).collect();
                 let v1 = d1.bulk_insert(items.clone().into_iter());
-                let v2 = d2.bulk_insert(items.into_iter());
-                cmp!(v1, v2);
+                let v2 = d2.bulk_insert(items.clone().into_iter());
+                let v3 = d3.bulk_insert(items.into_iter());
+                cmp!(v1, v2, v3);
+                allow_dangling_edges = true;
             }
             Op::CreateVertex(vertex) => {
                 let vertex = vertex.into();
                 let v1 = t1.create_vertex(&vertex);
                 let v2 = t2.create_vertex(&vertex);
-                cmp!(v1, v2);
+                let v3 = t3.create_vertex(&vertex);
+                cmp!(v1, v2, v3);
             }
             Op::GetVertices(q) => {
                 let q: indradb::VertexQuery = q.into();
                 let v1 = t1.get_vertices(q.clone());
-                let v2 = t2.get_vertices(q);
-                cmp!(v1, v2);
+                let v2 = t2.get_vertices(q.clone());
+                let v3 = t3.get_vertices(q);
+                cmp!(v1, v2, v3);
             }
             Op::DeleteVertices(q) => {
                 let q: indradb::VertexQuery = q.into();
                 let v1 = t1.delete_vertices(q.clone());
-                let v2 = t2.delete_vertices(q);
-                cmp!(v1, v2);
+                let v2 = t2.delete_vertices(q.clone());
+                let v3 = t3.delete_vertices(q);
+                cmp!(v1, v2, v3);
             }
             Op::GetVertexCount => {
                 let v1 = t1.get_vertex_count();
                 let v2 = t2.get_vertex_count();
-                cmp!(v1, v2);
+                let v3 = t3.get_vertex_count();
+                cmp!(v1, v2, v3);
             }
             Op::CreateEdge(key) => {
                 let key: indradb::EdgeKey = key.into();
                 let v1 = t1.create_edge(&key);
                 let v2 = t2.create_edge(&key);
-                cmp!(v1, v2);
+                let v3 = t3.create_edge(&key);
+                cmp!(v1, v2, v3);
             }
             Op::GetEdges(q) => {
                 let q: indradb::EdgeQuery = q.into();
                 let v1 = t1.get_edges(q.clone());
-                let v2 = t2.get_edges(q);
-                cmp!(v1, v2);
+                let v2 = t2.get_edges(q.clone());
+                let v3 = t3.get_edges(q);
+                cmp!(v1, v2, v3);
             }
             Op::DeleteEdges(q) => {
                 let q: indradb::EdgeQuery = q.into();
                 let v1 = t1.delete_edges(q.clone());
-                let v2 = t2.delete_edges(q);
-                cmp!(v1, v2);
+                let v2 = t2.delete_edges(q.clone());
+                let v3 = t3.delete_edges(q);
+                cmp!(v1, v2, v3);
             }
             Op::GetEdgeCount(id, t, direction) => {
                 let id: uuid::Uuid = id.into();
@@ -573,58 +628,71 @@ fuzz_target!(|ops: Vec<Op>| {
                 let direction: indradb::EdgeDirection = direction.into();
                 let v1 = t1.get_edge_count(id, t.as_ref(), direction);
                 let v2 = t2.get_edge_count(id, t.as_ref(), direction);
-                cmp!(v1, v2);
+                let v3 = t3.get_edge_count(id, t.as_ref(), direction);
+                cmp!(v1, v2, v3);
             }
             Op::GetVertexProperties(q) => {
                 let q: indradb::VertexPropertyQuery = q.into();
                 let v1 = t1.get_vertex_properties(q.clone());
-                let v2 = t2.get_vertex_properties(q);
-                cmp!(v1, v2);
+                let v2 = t2.get_vertex_properties(q.clone());
+                let v3 = t3.get_vertex_properties(q);
+                cmp!(v1, v2, v3);
             }
             Op::GetAllVertexProperties(q) => {
                 let q: indradb::VertexQuery = q.into();
                 let v1 = t1.get_all_vertex_properties(q.clone());
-                let v2 = t2.get_all_vertex_properties(q);
-                cmp!(v1, v2);
+                let v2 = t2.get_all_vertex_properties(q.clone());
+                let v3 = t3.get_all_vertex_properties(q);
+                cmp!(v1, v2, v3);
             }
             Op::SetVertexProperties(q, value) => {
                 let q: indradb::VertexPropertyQuery = q.into();
                 let value: serde_json::Value = value.into();
                 let v1 = t1.set_vertex_properties(q.clone(), &value);
-                let v2 = t2.set_vertex_properties(q, &value);
-                cmp!(v1, v2);
+                let v2 = t2.set_vertex_properties(q.clone(), &value);
+                let v3 = t3.set_vertex_properties(q, &value);
+                cmp!(v1, v2, v3);
             }
             Op::DeleteVertexProperties(q) => {
                 let q: indradb::VertexPropertyQuery = q.into();
                 let v1 = t1.delete_vertex_properties(q.clone());
-                let v2 = t2.delete_vertex_properties(q);
-                cmp!(v1, v2);
+                let v2 = t2.delete_vertex_properties(q.clone());
+                let v3 = t3.delete_vertex_properties(q);
+                cmp!(v1, v2, v3);
             }
             Op::GetEdgeProperties(q) => {
                 let q: indradb::EdgePropertyQuery = q.into();
                 let v1 = t1.get_edge_properties(q.clone());
-                let v2 = t2.get_edge_properties(q);
-                cmp!(v1, v2);
+                let v2 = t2.get_edge_properties(q.clone());
+                let v3 = t3.get_edge_properties(q);
+                cmp!(v1, v2, v3);
             }
             Op::GetAllEdgeProperties(q) => {
                 let q: indradb::EdgeQuery = q.into();
                 let v1 = t1.get_all_edge_properties(q.clone());
-                let v2 = t2.get_all_edge_properties(q);
-                cmp!(v1, v2);
+                let v2 = t2.get_all_edge_properties(q.clone());
+                let v3 = t3.get_all_edge_properties(q);
+                cmp!(v1, v2, v3);
             }
             Op::SetEdgeProperties(q, value) => {
                 let q: indradb::EdgePropertyQuery = q.into();
                 let value: serde_json::Value = value.into();
                 let v1 = t1.set_edge_properties(q.clone(), &value);
-                let v2 = t2.set_edge_properties(q, &value);
-                cmp!(v1, v2);
+                let v2 = t2.set_edge_properties(q.clone(), &value);
+                let v3 = t3.set_edge_properties(q, &value);
+                cmp!(v1, v2, v3);
             }
             Op::DeleteEdgeProperties(q) => {
                 let q: indradb::EdgePropertyQuery = q.into();
                 let v1 = t1.delete_edge_properties(q.clone());
-                let v2 = t2.delete_edge_properties(q);
-                cmp!(v1, v2);
+                let v2 = t2.delete_edge_properties(q.clone());
+                let v3 = t3.delete_edge_properties(q);
+                cmp!(v1, v2, v3);
             }
         }
+
+        check_invariants(&t1, allow_dangling_edges);
+        check_invariants(&t2, allow_dangling_edges);
+        check_invariants(&t3, allow_dangling_edges);
     }
 });
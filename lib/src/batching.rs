@@ -0,0 +1,495 @@
+use crate::errors::Result;
+use crate::models;
+use crate::traits::{Datastore, Transaction};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// Writes waiting to be flushed, along with when the oldest of them arrived,
+// and the vertices and edges they create.
+#[derive(Debug, Default)]
+struct Buffer {
+    items: Vec<models::BulkInsertItem>,
+    started: Option<Instant>,
+    vertices: HashSet<Uuid>,
+    edges: HashSet<models::EdgeKey>,
+}
+
+#[derive(Debug)]
+struct Batcher<D: Datastore> {
+    datastore: D,
+    buffer: Mutex<Buffer>,
+    max_items: usize,
+    max_delay: Duration,
+}
+
+impl<D: Datastore> Batcher<D> {
+    fn push<I: IntoIterator<Item = models::BulkInsertItem>>(&self, items: I) -> Result<()> {
+        let mut buffer = self.buffer.lock().unwrap();
+
+        for item in items {
+            match item {
+                models::BulkInsertItem::Vertex(ref vertex) => {
+                    buffer.vertices.insert(vertex.id);
+                }
+                models::BulkInsertItem::Edge(ref key) => {
+                    buffer.edges.insert(key.clone());
+                }
+                _ => {}
+            }
+
+            buffer.items.push(item);
+        }
+
+        // Only start the clock once something is waiting, so that a write
+        // that buffered nothing doesn't make the next one flush early
+        if buffer.started.is_none() && !buffer.items.is_empty() {
+            buffer.started = Some(Instant::now());
+        }
+
+        let expired = match buffer.started {
+            Some(started) => started.elapsed() >= self.max_delay,
+            None => false,
+        };
+
+        if buffer.items.len() >= self.max_items || expired {
+            self.flush_buffer(&mut buffer)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> Result<()> {
+        let mut buffer = self.buffer.lock().unwrap();
+        self.flush_buffer(&mut buffer)
+    }
+
+    // The buffer lock is held for the duration of the bulk insert, so that
+    // concurrent writers can't reorder their writes around a flush.
+    fn flush_buffer(&self, buffer: &mut Buffer) -> Result<()> {
+        buffer.started = None;
+        buffer.vertices.clear();
+        buffer.edges.clear();
+
+        if buffer.items.is_empty() {
+            return Ok(());
+        }
+
+        let items = mem::take(&mut buffer.items);
+        self.datastore.bulk_insert(items.into_iter())
+    }
+
+    // Gets which of the given vertices exist, either in the buffer or in the
+    // underlying datastore. The buffer is checked first, so that vertices
+    // flushed in between are still found.
+    fn existing_vertices(&self, trans: &D::Trans, ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let mut existing: HashSet<Uuid> = {
+            let buffer = self.buffer.lock().unwrap();
+            ids.iter().filter(|id| buffer.vertices.contains(id)).cloned().collect()
+        };
+
+        let unbuffered: Vec<Uuid> = ids.iter().filter(|id| !existing.contains(id)).cloned().collect();
+
+        if !unbuffered.is_empty() {
+            let vertices = trans.get_vertices(models::SpecificVertexQuery::new(unbuffered))?;
+            existing.extend(vertices.into_iter().map(|v| v.id));
+        }
+
+        Ok(existing)
+    }
+
+    // Gets which of the given edges exist, either in the buffer or in the
+    // underlying datastore.
+    fn existing_edges(&self, trans: &D::Trans, keys: &[models::EdgeKey]) -> Result<HashSet<models::EdgeKey>> {
+        let mut existing: HashSet<models::EdgeKey> = {
+            let buffer = self.buffer.lock().unwrap();
+            keys.iter().filter(|key| buffer.edges.contains(key)).cloned().collect()
+        };
+
+        let unbuffered: Vec<models::EdgeKey> = keys.iter().filter(|key| !existing.contains(key)).cloned().collect();
+
+        if !unbuffered.is_empty() {
+            let edges = trans.get_edges(models::SpecificEdgeQuery::new(unbuffered))?;
+            existing.extend(edges.into_iter().map(|e| e.key));
+        }
+
+        Ok(existing)
+    }
+}
+
+impl<D: Datastore> Drop for Batcher<D> {
+    fn drop(&mut self) {
+        // Errors can't be surfaced from here; callers that care should call
+        // `flush` explicitly before dropping the datastore.
+        let _ = self.flush();
+    }
+}
+
+/// A datastore that combines small writes into bulk inserts.
+///
+/// Vertex creations from a type, edge creations, and property writes
+/// against specific vertices or edges are buffered and applied together
+/// through the underlying datastore's `bulk_insert`. Any other call,
+/// including `create_vertex`, flushes the buffer first, so reads always see
+/// earlier writes.
+///
+/// There's no background timer. The buffer is flushed when a write leaves
+/// `max_items` writes waiting, or arrives `max_delay` or more after the
+/// oldest buffered one; otherwise, a buffer that stops receiving writes is
+/// only flushed by the next read, an explicit `flush`, or dropping the
+/// datastore.
+///
+/// Edge creations and property writes check that the vertices or edges
+/// they refer to exist, either in the buffer or in the underlying
+/// datastore, so they report the same results as a transaction would:
+/// `create_edge` returns false if either vertex is missing, and properties
+/// aren't written for vertices or edges that don't exist. Each check is a
+/// point read against the underlying datastore. Buffered edges are
+/// timestamped when they're flushed, not when they're created.
+///
+/// The checks happen when the write is buffered, and aren't repeated when
+/// it's flushed. Deletes through this datastore flush first, so they're
+/// always ordered after earlier writes. But if a vertex is deleted directly
+/// through the underlying datastore while an edge to it or a property on it
+/// is buffered, the flush still writes the edge or property, leaving it
+/// dangling.
+///
+/// Errors surface from whichever call triggers the flush, and the writes in
+/// a failed flush are discarded. Anything still buffered when the datastore
+/// is dropped is flushed then, but any error is lost; call `flush` first to
+/// see it. Calls to the datastore's own `bulk_insert` are buffered as-is,
+/// without any checks.
+#[derive(Debug)]
+pub struct BatchingDatastore<D: Datastore> {
+    batcher: Arc<Batcher<D>>,
+}
+
+impl<D: Datastore> BatchingDatastore<D> {
+    /// Creates a new batching datastore.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    /// * `max_items`: The number of buffered writes that triggers a flush.
+    /// * `max_delay`: How long writes can be buffered for before the next
+    ///   write triggers a flush.
+    pub fn new(inner: D, max_items: usize, max_delay: Duration) -> Self {
+        BatchingDatastore {
+            batcher: Arc::new(Batcher {
+                datastore: inner,
+                buffer: Mutex::new(Buffer::default()),
+                max_items,
+                max_delay,
+            }),
+        }
+    }
+
+    /// Applies any buffered writes.
+    pub fn flush(&self) -> Result<()> {
+        self.batcher.flush()
+    }
+}
+
+impl<D: Datastore> Datastore for BatchingDatastore<D> {
+    type Trans = BatchingTransaction<D>;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        Ok(BatchingTransaction {
+            inner: self.batcher.datastore.transaction()?,
+            batcher: self.batcher.clone(),
+        })
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        self.batcher.push(items)
+    }
+}
+
+/// A transaction that buffers writes in a `BatchingDatastore`.
+#[derive(Debug)]
+pub struct BatchingTransaction<D: Datastore> {
+    inner: D::Trans,
+    batcher: Arc<Batcher<D>>,
+}
+
+impl<D: Datastore> BatchingTransaction<D> {
+    /// Applies any buffered writes.
+    pub fn flush(&self) -> Result<()> {
+        self.batcher.flush()
+    }
+}
+
+impl<D: Datastore> Transaction for BatchingTransaction<D> {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        // Whether the vertex already exists can't be known without a read,
+        // so this goes straight through
+        self.batcher.flush()?;
+        self.inner.create_vertex(vertex)
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        let vertex = models::Vertex::new(t);
+        let id = vertex.id;
+        self.batcher.push(Some(models::BulkInsertItem::Vertex(vertex)))?;
+        Ok(id)
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        self.batcher.flush()?;
+        self.inner.get_vertices(q)
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()> {
+        self.batcher.flush()?;
+        self.inner.delete_vertices(q)
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.batcher.flush()?;
+        self.inner.get_vertex_count()
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        self.batcher.flush()?;
+        self.inner.get_vertex_count_by_type(t)
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let existing = self
+            .batcher
+            .existing_vertices(&self.inner, &[key.outbound_id, key.inbound_id])?;
+
+        if !existing.contains(&key.outbound_id) || !existing.contains(&key.inbound_id) {
+            return Ok(false);
+        }
+
+        self.batcher.push(Some(models::BulkInsertItem::Edge(key.clone())))?;
+        Ok(true)
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        self.batcher.flush()?;
+        self.inner.get_edges(q)
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()> {
+        self.batcher.flush()?;
+        self.inner.delete_edges(q)
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        self.batcher.flush()?;
+        self.inner.get_edge_count(id, t, direction)
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        self.batcher.flush()?;
+        self.inner.get_vertex_properties(q)
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        self.batcher.flush()?;
+        self.inner.find_vertices_with_property(q, predicate)
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        self.batcher.flush()?;
+        self.inner.get_all_vertex_properties(q)
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()> {
+        if let models::VertexQuery::Specific(ref specific) = q.inner {
+            let existing = self.batcher.existing_vertices(&self.inner, &specific.ids)?;
+            let items = specific
+                .ids
+                .iter()
+                .filter(|id| existing.contains(id))
+                .map(|id| models::BulkInsertItem::VertexProperty(*id, q.name.clone(), value.clone()));
+            return self.batcher.push(items);
+        }
+
+        self.batcher.flush()?;
+        self.inner.set_vertex_properties(q, value)
+    }
+
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.batcher.flush()?;
+        self.inner.patch_vertex_properties(q, patch)
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<()> {
+        self.batcher.flush()?;
+        self.inner.delete_vertex_properties(q)
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        self.batcher.flush()?;
+        self.inner.get_edge_properties(q)
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        self.batcher.flush()?;
+        self.inner.get_all_edge_properties(q)
+    }
+
+    fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()> {
+        if let models::EdgeQuery::Specific(ref specific) = q.inner {
+            let existing = self.batcher.existing_edges(&self.inner, &specific.keys)?;
+            let items = specific
+                .keys
+                .iter()
+                .filter(|key| existing.contains(key))
+                .map(|key| models::BulkInsertItem::EdgeProperty(key.clone(), q.name.clone(), value.clone()));
+            return self.batcher.push(items);
+        }
+
+        self.batcher.flush()?;
+        self.inner.set_edge_properties(q, value)
+    }
+
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.batcher.flush()?;
+        self.inner.patch_edge_properties(q, patch)
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()> {
+        self.batcher.flush()?;
+        self.inner.delete_edge_properties(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BatchingDatastore;
+    use crate::{
+        Datastore, EdgeDirection, EdgeKey, EdgeQueryExt, MemoryDatastore, SpecificEdgeQuery, SpecificVertexQuery,
+        Transaction, Type, Vertex, VertexQueryExt,
+    };
+    use serde_json::Value as JsonValue;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn should_buffer_until_max_items() {
+        let memory = MemoryDatastore::default();
+        let datastore = BatchingDatastore::new(memory.clone(), 3, Duration::from_secs(3600));
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("foo").unwrap();
+        let outbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let inbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        assert_eq!(memory.transaction().unwrap().get_vertex_count().unwrap(), 0);
+        trans
+            .create_edge(&EdgeKey::new(outbound_id, t.clone(), inbound_id))
+            .unwrap();
+
+        let memory_trans = memory.transaction().unwrap();
+        assert_eq!(memory_trans.get_vertex_count().unwrap(), 2);
+        assert_eq!(
+            memory_trans
+                .get_edge_count(outbound_id, None, EdgeDirection::Outbound)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn should_flush_after_max_delay() {
+        let memory = MemoryDatastore::default();
+        let datastore = BatchingDatastore::new(memory.clone(), 100, Duration::from_millis(0));
+        let trans = datastore.transaction().unwrap();
+        trans.create_vertex_from_type(Type::new("foo").unwrap()).unwrap();
+        assert_eq!(memory.transaction().unwrap().get_vertex_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn should_not_start_the_delay_for_empty_writes() {
+        let memory = MemoryDatastore::default();
+        let datastore = BatchingDatastore::new(memory.clone(), 100, Duration::from_millis(50));
+        let trans = datastore.transaction().unwrap();
+        let missing_id = Vertex::new(Type::new("foo").unwrap()).id;
+
+        // Nothing is buffered, since the vertex doesn't exist
+        let q = SpecificVertexQuery::single(missing_id).property("foo");
+        trans.set_vertex_properties(q, &JsonValue::Bool(true)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        trans.create_vertex_from_type(Type::new("foo").unwrap()).unwrap();
+        assert_eq!(memory.transaction().unwrap().get_vertex_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn should_flush_before_reads() {
+        let datastore = BatchingDatastore::new(MemoryDatastore::default(), 100, Duration::from_secs(3600));
+        let trans = datastore.transaction().unwrap();
+        let id = trans.create_vertex_from_type(Type::new("foo").unwrap()).unwrap();
+        let q = SpecificVertexQuery::single(id).property("foo");
+        trans.set_vertex_properties(q.clone(), &JsonValue::Bool(true)).unwrap();
+        let properties = trans.get_vertex_properties(q).unwrap();
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].value, JsonValue::Bool(true));
+    }
+
+    #[test]
+    fn should_flush_on_drop() {
+        let memory = MemoryDatastore::default();
+        let datastore = BatchingDatastore::new(memory.clone(), 100, Duration::from_secs(3600));
+        datastore
+            .transaction()
+            .unwrap()
+            .create_vertex_from_type(Type::new("foo").unwrap())
+            .unwrap();
+        assert_eq!(memory.transaction().unwrap().get_vertex_count().unwrap(), 0);
+        drop(datastore);
+        assert_eq!(memory.transaction().unwrap().get_vertex_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn should_report_whether_writes_apply() {
+        let memory = MemoryDatastore::default();
+        let datastore = BatchingDatastore::new(memory.clone(), 100, Duration::from_secs(3600));
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("foo").unwrap();
+        let id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let missing_id = Vertex::new(t.clone()).id;
+
+        assert!(!trans.create_vertex(&Vertex::with_id(id, t.clone())).unwrap());
+        assert!(trans.create_edge(&EdgeKey::new(id, t.clone(), id)).unwrap());
+        assert!(!trans.create_edge(&EdgeKey::new(id, t.clone(), missing_id)).unwrap());
+
+        let q = SpecificVertexQuery::new(vec![id, missing_id]).property("foo");
+        trans.set_vertex_properties(q, &JsonValue::Bool(true)).unwrap();
+        let q = SpecificEdgeQuery::single(EdgeKey::new(missing_id, t, id)).property("foo");
+        trans.set_edge_properties(q, &JsonValue::Bool(true)).unwrap();
+        datastore.flush().unwrap();
+
+        let memory_trans = memory.transaction().unwrap();
+        let q = SpecificVertexQuery::new(vec![id, missing_id]).property("foo");
+        assert_eq!(memory_trans.get_vertex_properties(q).unwrap().len(), 1);
+        assert_eq!(memory_trans.get_vertex_count().unwrap(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "test-suite"))]
+mod suite {
+    use super::BatchingDatastore;
+    use crate::MemoryDatastore;
+    use std::time::Duration;
+
+    // Edges are timestamped when they're flushed, which the suite's checks
+    // of edge timestamps against the time of `create_edge` don't allow for,
+    // so flush on every write
+    full_test_impl!(BatchingDatastore::new(
+        MemoryDatastore::default(),
+        1,
+        Duration::from_secs(3600)
+    ));
+}
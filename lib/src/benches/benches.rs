@@ -1,4 +1,7 @@
-use crate::models::{BulkInsertItem, EdgeDirection, EdgeKey, SpecificEdgeQuery, SpecificVertexQuery, Type, Vertex};
+use crate::models::{
+    BulkInsertItem, EdgeDirection, EdgeKey, EdgeQueryExt, SpecificEdgeQuery, SpecificVertexQuery, Type, Vertex,
+    VertexQueryExt,
+};
 use crate::traits::{Datastore, Transaction};
 
 use serde_json::Value as JsonValue;
@@ -92,6 +95,111 @@ pub fn bench_get_edge_count<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
     });
 }
 
+fn bench_get_edge_range<D: Datastore>(b: &mut Bencher, datastore: &mut D, fan_out: usize) {
+    let t = Type::new("bench_get_edge_range").unwrap();
+    let outbound_v = Vertex::new(t.clone());
+    let mut items = vec![BulkInsertItem::Vertex(outbound_v.clone())];
+
+    for _ in 0..fan_out {
+        let inbound_v = Vertex::new(t.clone());
+        let key = EdgeKey::new(outbound_v.id, t.clone(), inbound_v.id);
+        items.push(BulkInsertItem::Vertex(inbound_v));
+        items.push(BulkInsertItem::Edge(key));
+    }
+
+    datastore.bulk_insert(items.into_iter()).unwrap();
+
+    b.iter(|| {
+        let trans = datastore.transaction().unwrap();
+        let q = SpecificVertexQuery::single(outbound_v.id).outbound().t(t.clone());
+        let edges = trans.get_edges(q).unwrap();
+        assert_eq!(edges.len(), fan_out);
+    });
+}
+
+pub fn bench_get_edge_range_10<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    bench_get_edge_range(b, datastore, 10);
+}
+
+pub fn bench_get_edge_range_100<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    bench_get_edge_range(b, datastore, 100);
+}
+
+pub fn bench_get_edge_range_1000<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    bench_get_edge_range(b, datastore, 1000);
+}
+
+pub fn bench_set_vertex_properties<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    let id = {
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("bench_set_vertex_properties").unwrap();
+        trans.create_vertex_from_type(t).unwrap()
+    };
+
+    b.iter(|| {
+        let trans = datastore.transaction().unwrap();
+        let q = SpecificVertexQuery::single(id).property("is_benchmark");
+        trans.set_vertex_properties(q, &JsonValue::Bool(true)).unwrap();
+    });
+}
+
+pub fn bench_get_vertex_properties<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    let id = {
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("bench_get_vertex_properties").unwrap();
+        let id = trans.create_vertex_from_type(t).unwrap();
+        let q = SpecificVertexQuery::single(id).property("is_benchmark");
+        trans.set_vertex_properties(q, &JsonValue::Bool(true)).unwrap();
+        id
+    };
+
+    b.iter(|| {
+        let trans = datastore.transaction().unwrap();
+        let q = SpecificVertexQuery::single(id).property("is_benchmark");
+        trans.get_vertex_properties(q).unwrap();
+    });
+}
+
+pub fn bench_set_edge_properties<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    let t = Type::new("bench_set_edge_properties").unwrap();
+
+    let key = {
+        let trans = datastore.transaction().unwrap();
+        let outbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let inbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let key = EdgeKey::new(outbound_id, t.clone(), inbound_id);
+        trans.create_edge(&key).unwrap();
+        key
+    };
+
+    b.iter(|| {
+        let trans = datastore.transaction().unwrap();
+        let q = SpecificEdgeQuery::single(key.clone()).property("is_benchmark");
+        trans.set_edge_properties(q, &JsonValue::Bool(true)).unwrap();
+    });
+}
+
+pub fn bench_get_edge_properties<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    let t = Type::new("bench_get_edge_properties").unwrap();
+
+    let key = {
+        let trans = datastore.transaction().unwrap();
+        let outbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let inbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let key = EdgeKey::new(outbound_id, t.clone(), inbound_id);
+        trans.create_edge(&key).unwrap();
+        let q = SpecificEdgeQuery::single(key.clone()).property("is_benchmark");
+        trans.set_edge_properties(q, &JsonValue::Bool(true)).unwrap();
+        key
+    };
+
+    b.iter(|| {
+        let trans = datastore.transaction().unwrap();
+        let q = SpecificEdgeQuery::single(key.clone()).property("is_benchmark");
+        trans.get_edge_properties(q).unwrap();
+    });
+}
+
 const BULK_INSERT_COUNT: usize = 100;
 
 pub fn bench_bulk_insert<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
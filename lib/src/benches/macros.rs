@@ -21,6 +21,13 @@ macro_rules! full_bench_impl {
         define_bench!(bench_create_edge, $code);
         define_bench!(bench_get_edges, $code);
         define_bench!(bench_get_edge_count, $code);
+        define_bench!(bench_get_edge_range_10, $code);
+        define_bench!(bench_get_edge_range_100, $code);
+        define_bench!(bench_get_edge_range_1000, $code);
+        define_bench!(bench_set_vertex_properties, $code);
+        define_bench!(bench_get_vertex_properties, $code);
+        define_bench!(bench_set_edge_properties, $code);
+        define_bench!(bench_get_edge_properties, $code);
         define_bench!(bench_bulk_insert, $code);
     };
 }
@@ -0,0 +1,645 @@
+use crate::errors::Result;
+use crate::models;
+use crate::traits::{Datastore, Transaction};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+type EdgeCountKey = (Uuid, Option<models::Type>, models::EdgeDirection);
+
+#[derive(Debug)]
+struct LruEntry<V> {
+    value: V,
+    tick: u64,
+    inserted: Instant,
+}
+
+// A least-recently-used map with an optional time-to-live. Recency is tracked
+// with a monotonically increasing tick, so that the least recently used entry
+// is always the first one in `order`.
+//
+// Keys are only admitted once they've been inserted `min_hits` times, so that
+// keys read once, e.g. by a scan, can't evict frequently read ones. The
+// counts of keys not yet admitted are halved whenever there are more of them
+// than the map's capacity, so that old reads are forgotten over time.
+#[derive(Debug)]
+struct Lru<K, V> {
+    capacity: usize,
+    ttl: Option<Duration>,
+    min_hits: u32,
+    tick: u64,
+    entries: HashMap<K, LruEntry<V>>,
+    order: BTreeMap<u64, K>,
+    candidates: HashMap<K, u32>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Lru<K, V> {
+    fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Lru {
+            capacity,
+            ttl,
+            min_hits: 1,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            candidates: HashMap::new(),
+        }
+    }
+
+    // Counts an insert of a key that isn't cached, and returns whether it
+    // has now been inserted often enough to be admitted.
+    fn admit(&mut self, key: &K) -> bool {
+        if self.min_hits <= 1 || self.entries.contains_key(key) {
+            return true;
+        }
+
+        let hits = self.candidates.entry(key.clone()).or_insert(0);
+        *hits += 1;
+
+        if *hits >= self.min_hits {
+            self.candidates.remove(key);
+            return true;
+        }
+
+        if self.candidates.len() > self.capacity {
+            self.candidates.retain(|_, hits| {
+                *hits /= 2;
+                *hits > 0
+            });
+        }
+
+        false
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let ttl = self.ttl;
+        let expired = match self.entries.get(key) {
+            Some(entry) => match ttl {
+                Some(ttl) => entry.inserted.elapsed() >= ttl,
+                None => false,
+            },
+            None => return None,
+        };
+
+        if expired {
+            self.remove(key);
+            return None;
+        }
+
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key).unwrap();
+        self.order.remove(&entry.tick);
+        self.order.insert(tick, key.clone());
+        entry.tick = tick;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 || !self.admit(&key) {
+            return;
+        }
+
+        self.remove(&key);
+
+        while self.entries.len() >= self.capacity {
+            let oldest_tick = *self.order.keys().next().unwrap();
+            let oldest_key = self.order.remove(&oldest_tick).unwrap();
+            self.entries.remove(&oldest_key);
+        }
+
+        let tick = self.next_tick();
+        self.order.insert(tick, key.clone());
+        self.entries.insert(
+            key,
+            LruEntry {
+                value,
+                tick,
+                inserted: Instant::now(),
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+// The cache state shared by a datastore and all of its transactions.
+//
+// `generation` is bumped on every invalidation. Readers note it before going
+// to the underlying datastore and only populate the cache if it hasn't
+// changed, so a read that races with a write can't cache a stale value.
+#[derive(Debug)]
+struct Cache {
+    generation: u64,
+    vertices: Lru<Uuid, Option<models::Vertex>>,
+    edges: Lru<models::EdgeKey, Option<models::Edge>>,
+    edge_counts: Lru<EdgeCountKey, u64>,
+}
+
+impl Cache {
+    fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Cache {
+            generation: 0,
+            vertices: Lru::new(capacity, ttl),
+            edges: Lru::new(capacity, ttl),
+            edge_counts: Lru::new(capacity, ttl),
+        }
+    }
+
+    fn set_min_hits(&mut self, min_hits: u32) {
+        self.vertices.min_hits = min_hits;
+        self.edges.min_hits = min_hits;
+        self.edge_counts.min_hits = min_hits;
+    }
+
+    fn invalidate_vertex(&mut self, id: Uuid) {
+        self.generation += 1;
+        self.vertices.remove(&id);
+    }
+
+    fn invalidate_edge(&mut self, key: &models::EdgeKey) {
+        self.generation += 1;
+        self.edges.remove(key);
+
+        for &(id, direction) in &[
+            (key.outbound_id, models::EdgeDirection::Outbound),
+            (key.inbound_id, models::EdgeDirection::Inbound),
+        ] {
+            self.edge_counts.remove(&(id, None, direction));
+            self.edge_counts.remove(&(id, Some(key.t.clone()), direction));
+        }
+    }
+
+    fn invalidate_all(&mut self) {
+        self.generation += 1;
+        self.vertices.clear();
+        self.edges.clear();
+        self.edge_counts.clear();
+    }
+}
+
+/// A datastore that caches point lookups in memory.
+///
+/// Specific vertex and edge queries, and edge counts, are served from a
+/// least-recently-used cache shared by all of the datastore's transactions.
+/// Writes made through the datastore invalidate the affected entries; writes
+/// made to the underlying datastore by anything else are only picked up once
+/// the entries expire, so set a time-to-live if that can happen.
+///
+/// By default, everything that's looked up is cached, so a single scan over
+/// many rarely read vertices can evict frequently read ones. To keep only
+/// the frequently read ("hot") entries, set a minimum number of lookups with
+/// `with_min_hits`; entries are then only cached once they've missed that
+/// many times, and an invalidated entry has to earn its place again.
+///
+/// Properties are not cached.
+#[derive(Debug)]
+pub struct CachedDatastore<D: Datastore> {
+    inner: D,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl<D: Datastore> CachedDatastore<D> {
+    /// Creates a new cached datastore.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    /// * `capacity`: The maximum number of vertices, edges and edge counts to
+    ///   cache, each.
+    pub fn new(inner: D, capacity: usize) -> Self {
+        CachedDatastore {
+            inner,
+            cache: Arc::new(Mutex::new(Cache::new(capacity, None))),
+        }
+    }
+
+    /// Creates a new cached datastore whose entries expire after a fixed
+    /// time.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    /// * `capacity`: The maximum number of vertices, edges and edge counts to
+    ///   cache, each.
+    /// * `ttl`: How long an entry stays valid after it has been cached.
+    pub fn with_ttl(inner: D, capacity: usize, ttl: Duration) -> Self {
+        CachedDatastore {
+            inner,
+            cache: Arc::new(Mutex::new(Cache::new(capacity, Some(ttl)))),
+        }
+    }
+
+    /// Only caches entries once they've been looked up a given number of
+    /// times, so that rarely read entries don't evict frequently read ones.
+    ///
+    /// # Arguments
+    /// * `min_hits`: How many times an entry has to be looked up before
+    ///   it's cached. 0 and 1 both cache everything.
+    pub fn with_min_hits(self, min_hits: u32) -> Self {
+        self.cache.lock().unwrap().set_min_hits(min_hits);
+        self
+    }
+
+    /// Drops all cached entries.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().invalidate_all();
+    }
+}
+
+impl<D: Datastore> Datastore for CachedDatastore<D> {
+    type Trans = CachedTransaction<D::Trans>;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        Ok(CachedTransaction {
+            inner: self.inner.transaction()?,
+            cache: self.cache.clone(),
+        })
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let result = self.inner.bulk_insert(items);
+        self.cache.lock().unwrap().invalidate_all();
+        result
+    }
+}
+
+/// A transaction that serves point lookups from, and invalidates, a
+/// `CachedDatastore`'s cache.
+#[derive(Debug)]
+pub struct CachedTransaction<T: Transaction> {
+    inner: T,
+    cache: Arc<Mutex<Cache>>,
+}
+
+// Looks up a list of keys through one of the cache's LRUs, fetching any
+// misses from the underlying datastore in a single call. Results are
+// returned in the order of `keys`, skipping keys that don't exist.
+fn get_through_cache<K, V, S, F, G>(
+    cache: &Mutex<Cache>,
+    select: S,
+    keys: Vec<K>,
+    fetch: F,
+    key_of: G,
+) -> Result<Vec<V>>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    S: Fn(&mut Cache) -> &mut Lru<K, Option<V>>,
+    F: FnOnce(Vec<K>) -> Result<Vec<V>>,
+    G: Fn(&V) -> K,
+{
+    let mut known: HashMap<K, Option<V>> = HashMap::with_capacity(keys.len());
+    let mut missing: Vec<K> = Vec::new();
+
+    let generation = {
+        let mut cache = cache.lock().unwrap();
+
+        for key in &keys {
+            if known.contains_key(key) {
+                continue;
+            }
+
+            match select(&mut cache).get(key) {
+                Some(value) => {
+                    known.insert(key.clone(), value);
+                }
+                None => {
+                    known.insert(key.clone(), None);
+                    missing.push(key.clone());
+                }
+            }
+        }
+
+        cache.generation
+    };
+
+    if !missing.is_empty() {
+        for value in fetch(missing.clone())? {
+            known.insert(key_of(&value), Some(value));
+        }
+
+        let mut cache = cache.lock().unwrap();
+
+        if cache.generation == generation {
+            for key in missing {
+                let value = known[&key].clone();
+                select(&mut cache).insert(key, value);
+            }
+        }
+    }
+
+    Ok(keys.iter().filter_map(|key| known[key].clone()).collect())
+}
+
+impl<T: Transaction> Transaction for CachedTransaction<T> {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        let result = self.inner.create_vertex(vertex);
+        self.cache.lock().unwrap().invalidate_vertex(vertex.id);
+        result
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        let id = self.inner.create_vertex_from_type(t)?;
+        self.cache.lock().unwrap().invalidate_vertex(id);
+        Ok(id)
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        match q.into() {
+            models::VertexQuery::Specific(specific) => get_through_cache(
+                &self.cache,
+                |cache| &mut cache.vertices,
+                specific.ids,
+                |ids| self.inner.get_vertices(models::SpecificVertexQuery::new(ids)),
+                |vertex| vertex.id,
+            ),
+            q => self.inner.get_vertices(q),
+        }
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()> {
+        let result = self.inner.delete_vertices(q);
+        self.cache.lock().unwrap().invalidate_all();
+        result
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.inner.get_vertex_count()
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        self.inner.get_vertex_count_by_type(t)
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let result = self.inner.create_edge(key);
+        self.cache.lock().unwrap().invalidate_edge(key);
+        result
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        match q.into() {
+            models::EdgeQuery::Specific(specific) => get_through_cache(
+                &self.cache,
+                |cache| &mut cache.edges,
+                specific.keys,
+                |keys| self.inner.get_edges(models::SpecificEdgeQuery::new(keys)),
+                |edge| edge.key.clone(),
+            ),
+            q => self.inner.get_edges(q),
+        }
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()> {
+        let result = self.inner.delete_edges(q);
+        self.cache.lock().unwrap().invalidate_all();
+        result
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        let key = (id, t.cloned(), direction);
+
+        let generation = {
+            let mut cache = self.cache.lock().unwrap();
+
+            if let Some(count) = cache.edge_counts.get(&key) {
+                return Ok(count);
+            }
+
+            cache.generation
+        };
+
+        let count = self.inner.get_edge_count(id, t, direction)?;
+        let mut cache = self.cache.lock().unwrap();
+
+        if cache.generation == generation {
+            cache.edge_counts.insert(key, count);
+        }
+
+        Ok(count)
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        self.inner.get_vertex_properties(q)
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        self.inner.find_vertices_with_property(q, predicate)
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        self.inner.get_all_vertex_properties(q)
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()> {
+        self.inner.set_vertex_properties(q, value)
+    }
+
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.inner.patch_vertex_properties(q, patch)
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<()> {
+        self.inner.delete_vertex_properties(q)
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        self.inner.get_edge_properties(q)
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        self.inner.get_all_edge_properties(q)
+    }
+
+    fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()> {
+        self.inner.set_edge_properties(q, value)
+    }
+
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.inner.patch_edge_properties(q, patch)
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()> {
+        self.inner.delete_edge_properties(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CachedDatastore, Lru};
+    use crate::metrics::{AggregateMetricsSink, MeteredDatastore};
+    use crate::{Datastore, EdgeKey, MemoryDatastore, SpecificEdgeQuery, SpecificVertexQuery, Transaction, Type};
+    use crate::{EdgeDirection, Vertex};
+    use std::sync::Arc;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn should_evict_least_recently_used() {
+        let mut lru = Lru::new(2, None);
+        lru.insert(1, "a");
+        lru.insert(2, "b");
+        assert_eq!(lru.get(&1), Some("a"));
+        lru.insert(3, "c");
+        assert_eq!(lru.get(&1), Some("a"));
+        assert_eq!(lru.get(&2), None);
+        assert_eq!(lru.get(&3), Some("c"));
+    }
+
+    #[test]
+    fn should_only_admit_frequently_used() {
+        let mut lru = Lru::new(2, None);
+        lru.min_hits = 2;
+
+        for key in &[1, 2, 1, 2] {
+            lru.insert(*key, "hot");
+        }
+
+        // A scan over keys that are each read once doesn't evict the hot
+        // ones
+        for key in 3..10 {
+            lru.insert(key, "cold");
+        }
+
+        assert_eq!(lru.get(&1), Some("hot"));
+        assert_eq!(lru.get(&2), Some("hot"));
+        assert_eq!(lru.get(&3), None);
+        assert!(lru.candidates.len() <= 3);
+    }
+
+    #[test]
+    fn should_expire_entries() {
+        let mut lru = Lru::new(2, Some(Duration::from_millis(10)));
+        lru.insert(1, "a");
+        sleep(Duration::from_millis(20));
+        assert_eq!(lru.get(&1), None);
+    }
+
+    #[test]
+    fn should_serve_repeated_lookups_from_cache() {
+        let sink = Arc::new(AggregateMetricsSink::default());
+        let datastore = CachedDatastore::new(MeteredDatastore::new(MemoryDatastore::default(), sink.clone()), 100);
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("foo").unwrap();
+        let outbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let inbound_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let key = EdgeKey::new(outbound_id, t.clone(), inbound_id);
+        trans.create_edge(&key).unwrap();
+
+        for _ in 0..3 {
+            let vertices = trans
+                .get_vertices(SpecificVertexQuery::new(vec![inbound_id, outbound_id]))
+                .unwrap();
+            assert_eq!(vertices.len(), 2);
+            assert_eq!(vertices[0].id, inbound_id);
+            assert_eq!(vertices[1].id, outbound_id);
+            assert_eq!(
+                trans.get_edges(SpecificEdgeQuery::single(key.clone())).unwrap().len(),
+                1
+            );
+            assert_eq!(
+                trans
+                    .get_edge_count(outbound_id, None, EdgeDirection::Outbound)
+                    .unwrap(),
+                1
+            );
+        }
+
+        let stats = sink.snapshot();
+        assert_eq!(stats["get_vertices"].calls, 1);
+        assert_eq!(stats["get_edges"].calls, 1);
+        assert_eq!(stats["get_edge_count"].calls, 1);
+    }
+
+    #[test]
+    fn should_cache_after_min_hits() {
+        let sink = Arc::new(AggregateMetricsSink::default());
+        let datastore =
+            CachedDatastore::new(MeteredDatastore::new(MemoryDatastore::default(), sink.clone()), 100).with_min_hits(2);
+        let trans = datastore.transaction().unwrap();
+        let id = trans.create_vertex_from_type(Type::new("foo").unwrap()).unwrap();
+
+        for _ in 0..4 {
+            assert_eq!(trans.get_vertices(SpecificVertexQuery::single(id)).unwrap().len(), 1);
+        }
+
+        assert_eq!(sink.snapshot()["get_vertices"].calls, 2);
+    }
+
+    #[test]
+    fn should_invalidate_on_writes() {
+        let datastore = CachedDatastore::new(MemoryDatastore::default(), 100);
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("foo").unwrap();
+        let vertex = Vertex::new(t.clone());
+        assert_eq!(
+            trans
+                .get_vertices(SpecificVertexQuery::single(vertex.id))
+                .unwrap()
+                .len(),
+            0
+        );
+        trans.create_vertex(&vertex).unwrap();
+        assert_eq!(
+            trans
+                .get_vertices(SpecificVertexQuery::single(vertex.id))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let other_id = trans.create_vertex_from_type(t.clone()).unwrap();
+        assert_eq!(
+            trans.get_edge_count(vertex.id, None, EdgeDirection::Outbound).unwrap(),
+            0
+        );
+        trans.create_edge(&EdgeKey::new(vertex.id, t, other_id)).unwrap();
+        assert_eq!(
+            trans.get_edge_count(vertex.id, None, EdgeDirection::Outbound).unwrap(),
+            1
+        );
+
+        trans.delete_vertices(SpecificVertexQuery::single(vertex.id)).unwrap();
+        assert_eq!(
+            trans
+                .get_vertices(SpecificVertexQuery::single(vertex.id))
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_eq!(
+            trans.get_edge_count(vertex.id, None, EdgeDirection::Outbound).unwrap(),
+            0
+        );
+    }
+}
+
+#[cfg(all(test, feature = "test-suite"))]
+mod suite {
+    use super::CachedDatastore;
+    use crate::MemoryDatastore;
+
+    full_test_impl!(CachedDatastore::new(MemoryDatastore::default(), 100));
+}
@@ -0,0 +1,154 @@
+//! Rendering subgraphs in the Graphviz DOT language.
+//!
+//! Vertices are labeled with their types and edges with theirs by default.
+//! To style the output differently - e.g. to label vertices with a property,
+//! or set edge widths from a weight property - implement `Style` on a type
+//! that holds whatever the styling needs, fetched beforehand.
+
+use crate::models::{Edge, Subgraph, Vertex};
+use std::io::{Result, Write};
+
+/// Decides the attributes of the vertices and edges in a rendered graph.
+pub trait Style {
+    /// Gets the DOT attributes of a vertex, as name/value pairs. By default,
+    /// vertices are labeled with their type.
+    ///
+    /// # Arguments
+    /// * `vertex` - The vertex.
+    fn vertex_attributes(&self, vertex: &Vertex) -> Vec<(String, String)> {
+        vec![("label".to_string(), vertex.t.0.clone())]
+    }
+
+    /// Gets the DOT attributes of an edge, as name/value pairs. By default,
+    /// edges are labeled with their type.
+    ///
+    /// # Arguments
+    /// * `edge` - The edge.
+    fn edge_attributes(&self, edge: &Edge) -> Vec<(String, String)> {
+        vec![("label".to_string(), edge.key.t.0.clone())]
+    }
+}
+
+/// The default style, which labels vertices and edges with their types.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultStyle;
+
+impl Style for DefaultStyle {}
+
+/// Writes a subgraph as a DOT digraph. Vertices are identified by their
+/// ids.
+///
+/// # Arguments
+/// * `subgraph` - The subgraph to write.
+/// * `style` - Decides the attributes of the vertices and edges.
+/// * `writer` - Where to write the graph.
+pub fn export<S: Style + ?Sized, W: Write>(subgraph: &Subgraph, style: &S, writer: &mut W) -> Result<()> {
+    writeln!(writer, "digraph {{")?;
+
+    for vertex in &subgraph.vertices {
+        writeln!(
+            writer,
+            "    \"{}\"{};",
+            vertex.id,
+            format_attributes(&style.vertex_attributes(vertex))
+        )?;
+    }
+
+    for edge in &subgraph.edges {
+        writeln!(
+            writer,
+            "    \"{}\" -> \"{}\"{};",
+            edge.key.outbound_id,
+            edge.key.inbound_id,
+            format_attributes(&style.edge_attributes(edge))
+        )?;
+    }
+
+    writeln!(writer, "}}")
+}
+
+fn format_attributes(attributes: &[(String, String)]) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+
+    let attributes: Vec<String> = attributes
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", escape(name), escape(value)))
+        .collect();
+    format!(" [{}]", attributes.join(", "))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, DefaultStyle, Style};
+    use crate::{Edge, EdgeKey, Subgraph, Type, Vertex};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    struct WidthStyle;
+
+    impl Style for WidthStyle {
+        fn vertex_attributes(&self, _: &Vertex) -> Vec<(String, String)> {
+            Vec::new()
+        }
+
+        fn edge_attributes(&self, _: &Edge) -> Vec<(String, String)> {
+            vec![
+                ("penwidth".to_string(), "2.5".to_string()),
+                ("label".to_string(), "say \"hi\"".to_string()),
+            ]
+        }
+    }
+
+    fn subgraph() -> (Uuid, Uuid, Subgraph) {
+        let t = Type::new("user").unwrap();
+        let a = Vertex::with_id(Uuid::from_u128(1), t.clone());
+        let b = Vertex::with_id(Uuid::from_u128(2), t);
+        let edge = Edge::new(EdgeKey::new(a.id, Type::new("follows").unwrap(), b.id), Utc::now());
+        (a.id, b.id, Subgraph::new(vec![a, b], vec![edge]))
+    }
+
+    #[test]
+    fn should_export_with_the_default_style() {
+        let (a, b, subgraph) = subgraph();
+        let mut out = Vec::new();
+        export(&subgraph, &DefaultStyle, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "digraph {{\n    \"{a}\" [label=\"user\"];\n    \"{b}\" [label=\"user\"];\n    \"{a}\" -> \"{b}\" [label=\"follows\"];\n}}\n",
+                a = a,
+                b = b
+            )
+        );
+    }
+
+    #[test]
+    fn should_export_with_a_custom_style() {
+        let (a, b, subgraph) = subgraph();
+        let mut out = Vec::new();
+        export(&subgraph, &WidthStyle, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "digraph {{\n    \"{a}\";\n    \"{b}\";\n    \"{a}\" -> \"{b}\" [penwidth=\"2.5\", label=\"say \\\"hi\\\"\"];\n}}\n",
+                a = a,
+                b = b
+            )
+        );
+    }
+
+    #[test]
+    fn should_export_an_empty_subgraph() {
+        let mut out = Vec::new();
+        export(&Subgraph::default(), &DefaultStyle, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "digraph {\n}\n");
+    }
+}
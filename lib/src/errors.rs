@@ -18,6 +18,18 @@ pub enum Error {
     Sled { inner: SledError },
     #[fail(display = "UUID already taken")]
     UuidTaken,
+    #[fail(display = "transaction is read-only")]
+    ReadOnly,
+    #[fail(display = "schema violation: {}", message)]
+    SchemaViolation { message: String },
+    #[fail(display = "uniqueness violation: {}", message)]
+    UniquenessViolation { message: String },
+    #[fail(display = "limit of {} is over the maximum of {}", limit, max)]
+    LimitExceeded { limit: u64, max: u64 },
+    #[fail(display = "depth of {} is over the maximum of {}", depth, max)]
+    DepthExceeded { depth: u32, max: u32 },
+    #[fail(display = "property of {} bytes is over the maximum of {} bytes", size, max)]
+    PropertyTooLarge { size: usize, max: usize },
 }
 
 impl From<JsonError> for Error {
@@ -0,0 +1,693 @@
+//! A subset of the Gremlin traversal language.
+//!
+//! Traversals are parsed from strings like
+//! `g.V().hasLabel('user').out('follows').limit(10)` and run against any
+//! transaction, by translating them onto vertex and edge queries. The
+//! supported steps are:
+//!
+//! * `V()`, or `V(id, ...)`, to start from all vertices or specific ones.
+//! * `out(t)`, `in(t)`, `outE(t)` and `inE(t)`, where the edge type is
+//!   optional.
+//! * `outV()` and `inV()`, to get the vertices at either end of edges.
+//! * `hasLabel(t)`, `has(name)` and `has(name, value)`, to filter vertices
+//!   or edges by type, by whether they have a property, or by a property's
+//!   value.
+//! * `limit(n)`.
+//! * `count()`, which can only come last.
+//!
+//! Steps translate onto queries wherever possible, so that e.g. `out()`
+//! after `V(id)` becomes a single pipe query. Steps that queries can't
+//! express, such as property filters, run the traversal so far and continue
+//! from the matching vertices or edges.
+
+use crate::errors::Result;
+use crate::models;
+use crate::models::{EdgeQueryExt, VertexQueryExt};
+use crate::traits::Transaction;
+use failure::Fail;
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::iter::Peekable;
+use std::str::CharIndices;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// An error parsing a traversal.
+#[derive(Debug, Fail)]
+#[fail(display = "{} at position {}", message, position)]
+pub struct ParseError {
+    /// What went wrong.
+    pub message: String,
+    /// The byte offset in the traversal string where it went wrong.
+    pub position: usize,
+}
+
+impl ParseError {
+    fn new<S: Into<String>>(message: S, position: usize) -> Self {
+        ParseError {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+/// A single step of a traversal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Starts from the vertices with the given ids, or all vertices if
+    /// there are none.
+    V(Vec<Uuid>),
+    /// Follows outbound edges, optionally of a type, to their vertices.
+    Out(Option<models::Type>),
+    /// Follows inbound edges, optionally of a type, to their vertices.
+    In(Option<models::Type>),
+    /// Gets outbound edges, optionally of a type.
+    OutE(Option<models::Type>),
+    /// Gets inbound edges, optionally of a type.
+    InE(Option<models::Type>),
+    /// Gets the outbound vertices of edges.
+    OutV,
+    /// Gets the inbound vertices of edges.
+    InV,
+    /// Filters by type.
+    HasLabel(models::Type),
+    /// Filters by whether a property exists, or has a value.
+    Has(String, Option<JsonValue>),
+    /// Limits the number of results.
+    Limit(u32),
+    /// Counts the results.
+    Count,
+}
+
+/// The result of running a traversal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraversalResult {
+    Vertices(Vec<models::Vertex>),
+    Edges(Vec<models::Edge>),
+    Count(u64),
+}
+
+/// A parsed traversal. Traversals can only be created by parsing, so their
+/// steps are always valid for the kind of stream they're applied to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Traversal {
+    steps: Vec<Step>,
+}
+
+impl FromStr for Traversal {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Parser::new(s).parse()
+    }
+}
+
+impl Traversal {
+    /// Parses a traversal.
+    ///
+    /// # Arguments
+    /// * `s` - The traversal, e.g. `g.V().out('follows')`.
+    pub fn parse(s: &str) -> std::result::Result<Self, ParseError> {
+        s.parse()
+    }
+
+    /// Gets the steps of the traversal, in order. The first is always `V`.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Runs the traversal.
+    ///
+    /// # Arguments
+    /// * `trans` - The transaction to run the traversal in.
+    pub fn execute<T: Transaction>(&self, trans: &T) -> Result<TraversalResult> {
+        let mut stream = Stream::Vertices(models::RangeVertexQuery::new().into());
+
+        for step in &self.steps {
+            stream = match (step, stream) {
+                (Step::V(ids), _) if ids.is_empty() => Stream::Vertices(models::RangeVertexQuery::new().into()),
+                (Step::V(ids), _) => Stream::Vertices(models::SpecificVertexQuery::new(ids.clone()).into()),
+                (Step::Out(t), Stream::Vertices(q)) => {
+                    Stream::Vertices(with_edge_type(q.outbound(), t).inbound().into())
+                }
+                (Step::In(t), Stream::Vertices(q)) => {
+                    Stream::Vertices(with_edge_type(q.inbound(), t).outbound().into())
+                }
+                (Step::OutE(t), Stream::Vertices(q)) => Stream::Edges(with_edge_type(q.outbound(), t).into()),
+                (Step::InE(t), Stream::Vertices(q)) => Stream::Edges(with_edge_type(q.inbound(), t).into()),
+                (Step::OutV, Stream::Edges(q)) => Stream::Vertices(q.outbound().into()),
+                (Step::InV, Stream::Edges(q)) => Stream::Vertices(q.inbound().into()),
+                (Step::HasLabel(t), Stream::Vertices(q)) => Stream::Vertices(filter_vertex_type(trans, q, t)?),
+                (Step::HasLabel(t), Stream::Edges(q)) => Stream::Edges(filter_edge_type(trans, q, t)?),
+                (Step::Has(name, value), Stream::Vertices(q)) => {
+                    Stream::Vertices(filter_vertex_property(trans, q, name, value.as_ref())?)
+                }
+                (Step::Has(name, value), Stream::Edges(q)) => {
+                    Stream::Edges(filter_edge_property(trans, q, name, value.as_ref())?)
+                }
+                (Step::Limit(limit), Stream::Vertices(q)) => Stream::Vertices(limit_vertices(trans, q, *limit)?),
+                (Step::Limit(limit), Stream::Edges(q)) => Stream::Edges(limit_edges(trans, q, *limit)?),
+                (Step::Count, Stream::Vertices(q)) => {
+                    return Ok(TraversalResult::Count(trans.get_vertices(q)?.len() as u64))
+                }
+                (Step::Count, Stream::Edges(q)) => return Ok(TraversalResult::Count(trans.get_edges(q)?.len() as u64)),
+                // The parser rejects traversals that apply steps to the wrong
+                // kind of stream
+                (step, _) => unreachable!("step `{:?}` applied to the wrong kind of stream", step),
+            };
+        }
+
+        match stream {
+            Stream::Vertices(q) => Ok(TraversalResult::Vertices(trans.get_vertices(q)?)),
+            Stream::Edges(q) => Ok(TraversalResult::Edges(trans.get_edges(q)?)),
+        }
+    }
+}
+
+enum Stream {
+    Vertices(models::VertexQuery),
+    Edges(models::EdgeQuery),
+}
+
+fn with_edge_type(q: models::PipeEdgeQuery, t: &Option<models::Type>) -> models::PipeEdgeQuery {
+    match t {
+        Some(t) => q.t(t.clone()),
+        None => q,
+    }
+}
+
+fn filter_vertex_type<T: Transaction>(
+    trans: &T,
+    q: models::VertexQuery,
+    t: &models::Type,
+) -> Result<models::VertexQuery> {
+    // Add the filter to the query itself if doing so won't change which
+    // results the query's limit applies to
+    match q {
+        models::VertexQuery::Range(ref range) if range.t.is_none() && range.limit == u32::MAX => {
+            Ok(range.clone().t(t.clone()).into())
+        }
+        models::VertexQuery::Pipe(ref pipe) if pipe.t.is_none() && pipe.limit == u32::MAX => {
+            Ok(pipe.clone().t(t.clone()).into())
+        }
+        _ => {
+            let ids = trans
+                .get_vertices(q)?
+                .into_iter()
+                .filter(|v| &v.t == t)
+                .map(|v| v.id)
+                .collect();
+            Ok(models::SpecificVertexQuery::new(ids).into())
+        }
+    }
+}
+
+fn filter_edge_type<T: Transaction>(trans: &T, q: models::EdgeQuery, t: &models::Type) -> Result<models::EdgeQuery> {
+    match q {
+        models::EdgeQuery::Pipe(ref pipe) if pipe.t.is_none() && pipe.limit == u32::MAX => {
+            Ok(pipe.clone().t(t.clone()).into())
+        }
+        _ => {
+            let keys = trans
+                .get_edges(q)?
+                .into_iter()
+                .filter(|e| &e.key.t == t)
+                .map(|e| e.key)
+                .collect();
+            Ok(models::SpecificEdgeQuery::new(keys).into())
+        }
+    }
+}
+
+fn filter_vertex_property<T: Transaction>(
+    trans: &T,
+    q: models::VertexQuery,
+    name: &str,
+    value: Option<&JsonValue>,
+) -> Result<models::VertexQuery> {
+    let ids: Vec<Uuid> = trans.get_vertices(q)?.into_iter().map(|v| v.id).collect();
+    let matching: HashSet<Uuid> = trans
+        .get_vertex_properties(models::SpecificVertexQuery::new(ids.clone()).property(name))?
+        .into_iter()
+        .filter(|p| match value {
+            Some(value) => &p.value == value,
+            None => true,
+        })
+        .map(|p| p.id)
+        .collect();
+    let ids = ids.into_iter().filter(|id| matching.contains(id)).collect();
+    Ok(models::SpecificVertexQuery::new(ids).into())
+}
+
+fn filter_edge_property<T: Transaction>(
+    trans: &T,
+    q: models::EdgeQuery,
+    name: &str,
+    value: Option<&JsonValue>,
+) -> Result<models::EdgeQuery> {
+    let keys: Vec<models::EdgeKey> = trans.get_edges(q)?.into_iter().map(|e| e.key).collect();
+    let matching: HashSet<models::EdgeKey> = trans
+        .get_edge_properties(models::SpecificEdgeQuery::new(keys.clone()).property(name))?
+        .into_iter()
+        .filter(|p| match value {
+            Some(value) => &p.value == value,
+            None => true,
+        })
+        .map(|p| p.key)
+        .collect();
+    let keys = keys.into_iter().filter(|key| matching.contains(key)).collect();
+    Ok(models::SpecificEdgeQuery::new(keys).into())
+}
+
+fn limit_vertices<T: Transaction>(trans: &T, q: models::VertexQuery, limit: u32) -> Result<models::VertexQuery> {
+    match q {
+        models::VertexQuery::Range(range) => {
+            let limit = range.limit.min(limit);
+            Ok(range.limit(limit).into())
+        }
+        models::VertexQuery::Pipe(pipe) => {
+            let limit = pipe.limit.min(limit);
+            Ok(pipe.limit(limit).into())
+        }
+        models::VertexQuery::Specific(specific) => {
+            // Drop ids of vertices that don't exist first, so they don't
+            // count towards the limit
+            let existing: HashSet<Uuid> = trans
+                .get_vertices(specific.clone())?
+                .into_iter()
+                .map(|v| v.id)
+                .collect();
+            let ids = specific
+                .ids
+                .into_iter()
+                .filter(|id| existing.contains(id))
+                .take(limit as usize)
+                .collect();
+            Ok(models::SpecificVertexQuery::new(ids).into())
+        }
+    }
+}
+
+fn limit_edges<T: Transaction>(trans: &T, q: models::EdgeQuery, limit: u32) -> Result<models::EdgeQuery> {
+    match q {
+        models::EdgeQuery::Pipe(pipe) => {
+            let limit = pipe.limit.min(limit);
+            Ok(pipe.limit(limit).into())
+        }
+        models::EdgeQuery::Specific(specific) => {
+            let existing: HashSet<models::EdgeKey> =
+                trans.get_edges(specific.clone())?.into_iter().map(|e| e.key).collect();
+            let keys = specific
+                .keys
+                .into_iter()
+                .filter(|key| existing.contains(key))
+                .take(limit as usize)
+                .collect();
+            Ok(models::SpecificEdgeQuery::new(keys).into())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Arg {
+    String(String),
+    Number(JsonValue),
+    Bool(bool),
+}
+
+impl Arg {
+    fn into_json(self) -> JsonValue {
+        match self {
+            Arg::String(s) => JsonValue::String(s),
+            Arg::Number(n) => n,
+            Arg::Bool(b) => JsonValue::Bool(b),
+        }
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Parser {
+            s,
+            chars: s.char_indices().peekable(),
+        }
+    }
+
+    fn position(&mut self) -> usize {
+        match self.chars.peek() {
+            Some((i, _)) => *i,
+            None => self.s.len(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some((_, c)) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> std::result::Result<(), ParseError> {
+        self.skip_whitespace();
+        let position = self.position();
+
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            _ => Err(ParseError::new(format!("expected `{}`", expected), position)),
+        }
+    }
+
+    fn ident(&mut self) -> std::result::Result<String, ParseError> {
+        self.skip_whitespace();
+        let position = self.position();
+        let mut ident = String::new();
+
+        while let Some((_, c)) = self.chars.peek() {
+            if c.is_ascii_alphanumeric() || *c == '_' {
+                ident.push(*c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if ident.is_empty() {
+            Err(ParseError::new("expected a step name", position))
+        } else {
+            Ok(ident)
+        }
+    }
+
+    fn arg(&mut self) -> std::result::Result<Arg, ParseError> {
+        self.skip_whitespace();
+        let position = self.position();
+
+        match self.chars.peek().map(|(_, c)| *c) {
+            Some(quote) if quote == '\'' || quote == '"' => {
+                self.chars.next();
+                let mut s = String::new();
+
+                loop {
+                    match self.chars.next() {
+                        Some((_, '\\')) => match self.chars.next() {
+                            Some((_, c)) => s.push(c),
+                            None => return Err(ParseError::new("unterminated string", position)),
+                        },
+                        Some((_, c)) if c == quote => return Ok(Arg::String(s)),
+                        Some((_, c)) => s.push(c),
+                        None => return Err(ParseError::new("unterminated string", position)),
+                    }
+                }
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let mut s = String::new();
+
+                while let Some((_, c)) = self.chars.peek() {
+                    if *c == '-' || *c == '.' || *c == 'e' || *c == 'E' || *c == '+' || c.is_ascii_digit() {
+                        s.push(*c);
+                        self.chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                match serde_json::from_str::<JsonValue>(&s) {
+                    Ok(n) if n.is_number() => Ok(Arg::Number(n)),
+                    _ => Err(ParseError::new(format!("invalid number `{}`", s), position)),
+                }
+            }
+            _ => match self.ident()?.as_str() {
+                "true" => Ok(Arg::Bool(true)),
+                "false" => Ok(Arg::Bool(false)),
+                _ => Err(ParseError::new("expected a string, number or boolean", position)),
+            },
+        }
+    }
+
+    fn args(&mut self) -> std::result::Result<Vec<Arg>, ParseError> {
+        self.expect('(')?;
+        self.skip_whitespace();
+        let mut args = Vec::new();
+
+        if let Some((_, ')')) = self.chars.peek() {
+            self.chars.next();
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.arg()?);
+            self.skip_whitespace();
+            let position = self.position();
+
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, ')')) => return Ok(args),
+                _ => return Err(ParseError::new("expected `,` or `)`", position)),
+            }
+        }
+    }
+
+    fn parse(mut self) -> std::result::Result<Traversal, ParseError> {
+        let position = self.position();
+
+        if self.ident()? != "g" {
+            return Err(ParseError::new("traversals must start with `g`", position));
+        }
+
+        let mut steps = Vec::new();
+        let mut on_vertices = true;
+
+        loop {
+            self.skip_whitespace();
+
+            if self.chars.peek().is_none() {
+                break;
+            }
+
+            if steps.last() == Some(&Step::Count) {
+                return Err(ParseError::new("`count()` must be the last step", self.position()));
+            }
+
+            self.expect('.')?;
+            self.skip_whitespace();
+            let position = self.position();
+            let name = self.ident()?;
+            let args = self.args()?;
+
+            if steps.is_empty() && name != "V" {
+                return Err(ParseError::new("traversals must start with `V()`", position));
+            }
+
+            let step = match name.as_str() {
+                "V" => Step::V(uuid_args(args, position)?),
+                "out" => Step::Out(optional_type_arg(args, position)?),
+                "in" => Step::In(optional_type_arg(args, position)?),
+                "outE" => Step::OutE(optional_type_arg(args, position)?),
+                "inE" => Step::InE(optional_type_arg(args, position)?),
+                "outV" => no_args(args, Step::OutV, position)?,
+                "inV" => no_args(args, Step::InV, position)?,
+                "hasLabel" => match optional_type_arg(args, position)? {
+                    Some(t) => Step::HasLabel(t),
+                    None => return Err(ParseError::new("`hasLabel` takes a type", position)),
+                },
+                "has" => {
+                    let mut args = args.into_iter();
+
+                    match (args.next(), args.next(), args.next()) {
+                        (Some(Arg::String(name)), value, None) => Step::Has(name, value.map(Arg::into_json)),
+                        _ => {
+                            return Err(ParseError::new(
+                                "`has` takes a property name and an optional value",
+                                position,
+                            ))
+                        }
+                    }
+                }
+                "limit" => match args.as_slice() {
+                    [Arg::Number(n)] => match n.as_u64() {
+                        Some(n) if n <= u64::from(u32::MAX) => Step::Limit(n as u32),
+                        _ => return Err(ParseError::new("invalid limit", position)),
+                    },
+                    _ => return Err(ParseError::new("`limit` takes a number", position)),
+                },
+                "count" => no_args(args, Step::Count, position)?,
+                _ => return Err(ParseError::new(format!("unsupported step `{}`", name), position)),
+            };
+
+            let (requires_vertices, requires_edges) = match step {
+                Step::Out(_) | Step::In(_) | Step::OutE(_) | Step::InE(_) => (true, false),
+                Step::OutV | Step::InV => (false, true),
+                _ => (false, false),
+            };
+
+            if (requires_vertices && !on_vertices) || (requires_edges && on_vertices) {
+                let expected = if requires_vertices { "vertices" } else { "edges" };
+                return Err(ParseError::new(
+                    format!("`{}` can only follow {}", name, expected),
+                    position,
+                ));
+            }
+
+            on_vertices = match step {
+                Step::V(_) | Step::Out(_) | Step::In(_) | Step::OutV | Step::InV => true,
+                Step::OutE(_) | Step::InE(_) => false,
+                _ => on_vertices,
+            };
+
+            steps.push(step);
+        }
+
+        if steps.is_empty() {
+            return Err(ParseError::new("traversals must start with `V()`", self.position()));
+        }
+
+        Ok(Traversal { steps })
+    }
+}
+
+fn no_args(args: Vec<Arg>, step: Step, position: usize) -> std::result::Result<Step, ParseError> {
+    if args.is_empty() {
+        Ok(step)
+    } else {
+        Err(ParseError::new("step takes no arguments", position))
+    }
+}
+
+fn optional_type_arg(args: Vec<Arg>, position: usize) -> std::result::Result<Option<models::Type>, ParseError> {
+    let mut args = args.into_iter();
+
+    match (args.next(), args.next()) {
+        (None, _) => Ok(None),
+        (Some(Arg::String(t)), None) => match models::Type::new(t) {
+            Ok(t) => Ok(Some(t)),
+            Err(_) => Err(ParseError::new("invalid type", position)),
+        },
+        _ => Err(ParseError::new("step takes at most one type", position)),
+    }
+}
+
+fn uuid_args(args: Vec<Arg>, position: usize) -> std::result::Result<Vec<Uuid>, ParseError> {
+    args.into_iter()
+        .map(|arg| match arg {
+            Arg::String(s) => Uuid::parse_str(&s).map_err(|_| ParseError::new("invalid vertex id", position)),
+            _ => Err(ParseError::new("vertex ids must be strings", position)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Step, Traversal, TraversalResult};
+    use crate::{Datastore, EdgeKey, MemoryDatastore, SpecificVertexQuery, Transaction, Type, VertexQueryExt};
+    use serde_json::Value as JsonValue;
+
+    #[test]
+    fn should_parse_traversals() {
+        let traversal = Traversal::parse(
+            r#"g.V( ).hasLabel('user').has("age", 30).outE('follows').inV().has('active').limit(10).count()"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            traversal.steps().to_vec(),
+            vec![
+                Step::V(vec![]),
+                Step::HasLabel(Type::new("user").unwrap()),
+                Step::Has("age".to_string(), Some(JsonValue::from(30))),
+                Step::OutE(Some(Type::new("follows").unwrap())),
+                Step::InV,
+                Step::Has("active".to_string(), None),
+                Step::Limit(10),
+                Step::Count,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_parse_invalid_traversals() {
+        for s in &[
+            "",
+            "g",
+            "x.V()",
+            "g.out()",
+            "g.V().inV()",
+            "g.V().outE().out()",
+            "g.V().count().limit(1)",
+            "g.V().limit('a')",
+            "g.V().limit(-1)",
+            "g.V('not-a-uuid')",
+            "g.V().has()",
+            "g.V().out('a', 'b')",
+            "g.V().both()",
+            "g.V().out('unterminated",
+            "g.V(",
+        ] {
+            assert!(Traversal::parse(s).is_err(), "expected `{}` not to parse", s);
+        }
+    }
+
+    #[test]
+    fn should_execute_traversals() {
+        let datastore = MemoryDatastore::default();
+        let trans = datastore.transaction().unwrap();
+        let user_t = Type::new("user").unwrap();
+        let post_t = Type::new("post").unwrap();
+        let follows_t = Type::new("follows").unwrap();
+        let wrote_t = Type::new("wrote").unwrap();
+        let alice = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let bob = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let post = trans.create_vertex_from_type(post_t).unwrap();
+        trans.create_edge(&EdgeKey::new(alice, follows_t.clone(), bob)).unwrap();
+        trans.create_edge(&EdgeKey::new(bob, wrote_t, post)).unwrap();
+        trans
+            .set_vertex_properties(
+                SpecificVertexQuery::single(bob).property("name"),
+                &JsonValue::from("bob"),
+            )
+            .unwrap();
+
+        let run = |s: &str| Traversal::parse(s).unwrap().execute(&trans).unwrap();
+
+        assert_eq!(run("g.V().count()"), TraversalResult::Count(3));
+        assert_eq!(run("g.V().hasLabel('user').count()"), TraversalResult::Count(2));
+        assert_eq!(run("g.V().limit(1).count()"), TraversalResult::Count(1));
+        assert_eq!(run("g.V().has('name', 'bob').count()"), TraversalResult::Count(1));
+        assert_eq!(run("g.V().has('name', 'alice').count()"), TraversalResult::Count(0));
+
+        match run(&format!("g.V('{}').out('follows').out()", alice)) {
+            TraversalResult::Vertices(vertices) => {
+                assert_eq!(vertices.len(), 1);
+                assert_eq!(vertices[0].id, post);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        match run(&format!("g.V('{}').inE().hasLabel('follows')", bob)) {
+            TraversalResult::Edges(edges) => {
+                assert_eq!(edges.len(), 1);
+                assert_eq!(edges[0].key, EdgeKey::new(alice, follows_t, bob));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let missing = uuid::Uuid::default();
+        let s = format!("g.V('{}', '{}', '{}').limit(2).count()", missing, alice, bob);
+        assert_eq!(run(&s), TraversalResult::Count(2));
+
+        match run(&format!("g.V('{}').in().has('name')", post)) {
+            TraversalResult::Vertices(vertices) => {
+                assert_eq!(vertices.len(), 1);
+                assert_eq!(vertices[0].id, bob);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -19,15 +19,31 @@ pub mod tests;
 #[macro_use]
 pub mod benches;
 
+mod batching;
+mod cached;
+pub mod dot;
 mod errors;
+pub mod gremlin;
+mod limits;
 mod memory;
+mod metrics;
 mod models;
+mod read_only;
+mod schema;
 mod traits;
 pub mod util;
 
+pub use crate::batching::{BatchingDatastore, BatchingTransaction};
+pub use crate::cached::{CachedDatastore, CachedTransaction};
 pub use crate::errors::*;
+pub use crate::limits::{LimitedDatastore, LimitedTransaction, Limits};
 pub use crate::memory::{MemoryDatastore, MemoryTransaction};
+pub use crate::metrics::{
+    AggregateMetricsSink, Measurement, MeteredDatastore, MeteredTransaction, MetricsSink, OperationStats,
+};
 pub use crate::models::*;
+pub use crate::read_only::ReadOnlyTransaction;
+pub use crate::schema::{Schema, SchemaDatastore, SchemaTransaction};
 pub use crate::traits::*;
 
 #[cfg(any(feature = "rocksdb-datastore", feature = "sled-datastore"))]
@@ -36,9 +52,14 @@ mod bytes;
 mod rdb;
 #[cfg(feature = "sled-datastore")]
 mod sledds;
+#[cfg(feature = "tracing")]
+mod traced;
 
 #[cfg(feature = "rocksdb-datastore")]
 pub use crate::rdb::{RocksdbDatastore, RocksdbTransaction};
 
 #[cfg(feature = "sled-datastore")]
 pub use crate::sledds::{SledConfig, SledDatastore, SledTransaction};
+
+#[cfg(feature = "tracing")]
+pub use crate::traced::{TracedDatastore, TracedTransaction};
@@ -0,0 +1,587 @@
+use crate::errors::{Error, Result};
+use crate::models;
+use crate::traits::{Datastore, Transaction};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The largest queries and property values a `LimitedDatastore` accepts.
+///
+/// Everything is unlimited by default.
+#[derive(Clone, Debug)]
+pub struct Limits {
+    max_limit: u32,
+    max_depth: u32,
+    max_property_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_limit: u32::MAX,
+            max_depth: u32::MAX,
+            max_property_size: usize::MAX,
+        }
+    }
+}
+
+impl Limits {
+    /// Creates new limits, with everything unlimited.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the highest limit queries can have, and the most ids or edge
+    /// keys specific queries can have. Queries without an explicit limit
+    /// have the highest possible limit, so they have to set one.
+    ///
+    /// # Arguments
+    /// * `max_limit`: The maximum limit.
+    pub fn max_limit(mut self, max_limit: u32) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    /// Sets the most pipes a query can have, and the furthest subgraph
+    /// extractions and random walks can go.
+    ///
+    /// # Arguments
+    /// * `max_depth`: The maximum depth.
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the largest property value that can be written, as the length
+    /// of its JSON serialization in bytes. For patches, this applies to the
+    /// patch rather than the patched value.
+    ///
+    /// # Arguments
+    /// * `max_property_size`: The maximum size.
+    pub fn max_property_size(mut self, max_property_size: usize) -> Self {
+        self.max_property_size = max_property_size;
+        self
+    }
+
+    fn validate_limit(&self, limit: usize) -> Result<()> {
+        if limit > self.max_limit as usize {
+            Err(Error::LimitExceeded {
+                limit: limit as u64,
+                max: u64::from(self.max_limit),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_depth(&self, depth: u32) -> Result<()> {
+        if depth > self.max_depth {
+            Err(Error::DepthExceeded {
+                depth,
+                max: self.max_depth,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_property(&self, value: &JsonValue) -> Result<()> {
+        // Skip serializing if there's no limit to check against
+        if self.max_property_size == usize::MAX {
+            return Ok(());
+        }
+
+        let size = serde_json::to_vec(value)?.len();
+
+        if size > self.max_property_size {
+            Err(Error::PropertyTooLarge {
+                size,
+                max: self.max_property_size,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    // Validates a vertex query, returning the number of pipes in it.
+    fn validate_vertex_query(&self, q: &models::VertexQuery) -> Result<u32> {
+        let depth = match q {
+            models::VertexQuery::Range(range) => {
+                self.validate_limit(range.limit as usize)?;
+                0
+            }
+            models::VertexQuery::Specific(specific) => {
+                self.validate_limit(specific.ids.len())?;
+                0
+            }
+            models::VertexQuery::Pipe(pipe) => {
+                self.validate_limit(pipe.limit as usize)?;
+                self.validate_edge_query(&pipe.inner)? + 1
+            }
+        };
+
+        self.validate_depth(depth)?;
+        Ok(depth)
+    }
+
+    // Validates an edge query, returning the number of pipes in it.
+    fn validate_edge_query(&self, q: &models::EdgeQuery) -> Result<u32> {
+        let depth = match q {
+            models::EdgeQuery::Specific(specific) => {
+                self.validate_limit(specific.keys.len())?;
+                0
+            }
+            models::EdgeQuery::Pipe(pipe) => {
+                self.validate_limit(pipe.limit as usize)?;
+                self.validate_vertex_query(&pipe.inner)? + 1
+            }
+        };
+
+        self.validate_depth(depth)?;
+        Ok(depth)
+    }
+}
+
+/// A datastore that rejects queries and writes that go beyond some
+/// `Limits`, e.g. to stop clients of a server from reading an entire
+/// datastore in one query.
+///
+/// Only what callers pass in is checked. Queries that methods run
+/// internally, such as the pages read by `copy_into` or
+/// `get_vertex_count_by_type`, go straight to the wrapped datastore.
+#[derive(Debug)]
+pub struct LimitedDatastore<D: Datastore> {
+    inner: D,
+    limits: Arc<Limits>,
+}
+
+impl<D: Datastore> LimitedDatastore<D> {
+    /// Creates a new limited datastore.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    /// * `limits`: The limits to enforce.
+    pub fn new(inner: D, limits: Limits) -> Self {
+        LimitedDatastore {
+            inner,
+            limits: Arc::new(limits),
+        }
+    }
+
+    /// Gets the limits.
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+}
+
+impl<D: Datastore> Datastore for LimitedDatastore<D> {
+    type Trans = LimitedTransaction<D::Trans>;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        Ok(LimitedTransaction {
+            inner: self.inner.transaction()?,
+            limits: self.limits.clone(),
+        })
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let items: Vec<models::BulkInsertItem> = items.collect();
+
+        for item in &items {
+            match item {
+                models::BulkInsertItem::VertexProperty(_, _, ref value)
+                | models::BulkInsertItem::EdgeProperty(_, _, ref value) => self.limits.validate_property(value)?,
+                _ => {}
+            }
+        }
+
+        self.inner.bulk_insert(items.into_iter())
+    }
+
+    fn copy_into<O: Datastore>(&self, other: &O) -> Result<()> {
+        self.inner.copy_into(other)
+    }
+
+    fn check_integrity(&self) -> Result<Vec<models::EdgeKey>> {
+        self.inner.check_integrity()
+    }
+
+    fn repair_integrity(&self, t: &models::Type) -> Result<Vec<models::EdgeKey>> {
+        self.inner.repair_integrity(t)
+    }
+
+    fn diff<O: Datastore>(&self, other: &O) -> Result<Vec<models::Difference>> {
+        self.inner.diff(other)
+    }
+}
+
+/// A transaction that checks queries and writes against a
+/// `LimitedDatastore`'s limits.
+#[derive(Debug)]
+pub struct LimitedTransaction<T: Transaction> {
+    inner: T,
+    limits: Arc<Limits>,
+}
+
+impl<T: Transaction> Transaction for LimitedTransaction<T> {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        self.inner.create_vertex(vertex)
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        self.inner.create_vertex_from_type(t)
+    }
+
+    fn upsert_vertex_by_key(&self, key: &str, t: models::Type) -> Result<Uuid> {
+        self.inner.upsert_vertex_by_key(key, t)
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        let q = q.into();
+        self.limits.validate_vertex_query(&q)?;
+        self.inner.get_vertices(q)
+    }
+
+    fn get_vertices_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Option<models::Vertex>>> {
+        self.limits.validate_limit(ids.len())?;
+        self.inner.get_vertices_by_ids(ids)
+    }
+
+    fn sample_vertices(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Vertex>> {
+        self.limits.validate_limit(n as usize)?;
+        self.inner.sample_vertices(t, n)
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()> {
+        let q = q.into();
+        self.limits.validate_vertex_query(&q)?;
+        self.inner.delete_vertices(q)
+    }
+
+    fn merge_vertices(&self, survivor: Uuid, duplicates: Vec<Uuid>, policy: models::MergePolicy) -> Result<bool> {
+        self.limits.validate_limit(duplicates.len())?;
+        self.inner.merge_vertices(survivor, duplicates, policy)
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.inner.get_vertex_count()
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        self.inner.get_vertex_count_by_type(t)
+    }
+
+    fn get_vertex_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        self.inner.get_vertex_types()
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        self.inner.create_edge(key)
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        let q = q.into();
+        self.limits.validate_edge_query(&q)?;
+        self.inner.get_edges(q)
+    }
+
+    fn get_edges_by_keys(&self, keys: &[models::EdgeKey]) -> Result<Vec<Option<models::Edge>>> {
+        self.limits.validate_limit(keys.len())?;
+        self.inner.get_edges_by_keys(keys)
+    }
+
+    fn sample_edges(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Edge>> {
+        self.limits.validate_limit(n as usize)?;
+        self.inner.sample_edges(t, n)
+    }
+
+    fn get_edge_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        self.inner.get_edge_types()
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()> {
+        let q = q.into();
+        self.limits.validate_edge_query(&q)?;
+        self.inner.delete_edges(q)
+    }
+
+    fn extract_subgraph(&self, roots: Vec<Uuid>, depth: u32, edge_types: &[models::Type]) -> Result<models::Subgraph> {
+        self.limits.validate_limit(roots.len())?;
+        self.limits.validate_depth(depth)?;
+        self.inner.extract_subgraph(roots, depth, edge_types)
+    }
+
+    fn get_triangle_count(&self, id: Uuid, edge_types: &[models::Type]) -> Result<u64> {
+        self.inner.get_triangle_count(id, edge_types)
+    }
+
+    fn get_local_clustering_coefficient(&self, id: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        self.inner.get_local_clustering_coefficient(id, edge_types)
+    }
+
+    fn get_common_neighbors(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<Vec<Uuid>> {
+        self.inner.get_common_neighbors(a, b, edge_types)
+    }
+
+    fn get_jaccard_similarity(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        self.inner.get_jaccard_similarity(a, b, edge_types)
+    }
+
+    fn get_adamic_adar_index(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        self.inner.get_adamic_adar_index(a, b, edge_types)
+    }
+
+    fn random_walk(
+        &self,
+        start: Uuid,
+        length: u32,
+        edge_types: &[models::Type],
+        weight_property: Option<&str>,
+    ) -> Result<Vec<Uuid>> {
+        self.limits.validate_depth(length)?;
+        self.inner.random_walk(start, length, edge_types, weight_property)
+    }
+
+    fn get_adjacent(
+        &self,
+        id: Uuid,
+        t: Option<&models::Type>,
+        limit: u32,
+    ) -> Result<Vec<(models::EdgeDirection, models::Edge)>> {
+        self.limits.validate_limit(limit as usize)?;
+        self.inner.get_adjacent(id, t, limit)
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        self.inner.get_edge_count(id, t, direction)
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        self.limits.validate_vertex_query(&q.inner)?;
+        self.inner.get_vertex_properties(q)
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        self.limits.validate_vertex_query(&q.inner)?;
+        self.inner.find_vertices_with_property(q, predicate)
+    }
+
+    fn add_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        self.inner.add_vertex_label(id, label)
+    }
+
+    fn remove_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        self.inner.remove_vertex_label(id, label)
+    }
+
+    fn get_vertices_with_label<Q: Into<models::VertexQuery>>(&self, q: Q, label: &str) -> Result<Vec<models::Vertex>> {
+        let q = q.into();
+        self.limits.validate_vertex_query(&q)?;
+        self.inner.get_vertices_with_label(q, label)
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        let q = q.into();
+        self.limits.validate_vertex_query(&q)?;
+        self.inner.get_all_vertex_properties(q)
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()> {
+        self.limits.validate_vertex_query(&q.inner)?;
+        self.limits.validate_property(value)?;
+        self.inner.set_vertex_properties(q, value)
+    }
+
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.limits.validate_vertex_query(&q.inner)?;
+        self.limits.validate_property(patch)?;
+        self.inner.patch_vertex_properties(q, patch)
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<()> {
+        self.limits.validate_vertex_query(&q.inner)?;
+        self.inner.delete_vertex_properties(q)
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        self.limits.validate_edge_query(&q.inner)?;
+        self.inner.get_edge_properties(q)
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        let q = q.into();
+        self.limits.validate_edge_query(&q)?;
+        self.inner.get_all_edge_properties(q)
+    }
+
+    fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()> {
+        self.limits.validate_edge_query(&q.inner)?;
+        self.limits.validate_property(value)?;
+        self.inner.set_edge_properties(q, value)
+    }
+
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.limits.validate_edge_query(&q.inner)?;
+        self.limits.validate_property(patch)?;
+        self.inner.patch_edge_properties(q, patch)
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()> {
+        self.limits.validate_edge_query(&q.inner)?;
+        self.inner.delete_edge_properties(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LimitedDatastore, Limits};
+    use crate::{
+        BulkInsertItem, Datastore, EdgeQueryExt, Error, MemoryDatastore, RangeVertexQuery, SpecificVertexQuery,
+        Transaction, Type, Vertex, VertexQueryExt,
+    };
+    use serde_json::Value as JsonValue;
+
+    fn datastore() -> LimitedDatastore<MemoryDatastore> {
+        let limits = Limits::new().max_limit(10).max_depth(2).max_property_size(8);
+        LimitedDatastore::new(MemoryDatastore::default(), limits)
+    }
+
+    #[test]
+    fn should_enforce_query_limits() {
+        let datastore = datastore();
+        let trans = datastore.transaction().unwrap();
+        let id = trans.create_vertex_from_type(Type::new("user").unwrap()).unwrap();
+
+        assert_eq!(trans.get_vertices(RangeVertexQuery::new().limit(10)).unwrap().len(), 1);
+
+        match trans.get_vertices(RangeVertexQuery::new()) {
+            Err(Error::LimitExceeded { limit, max: 10 }) => assert_eq!(limit, u64::from(u32::MAX)),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let q = SpecificVertexQuery::new(vec![id; 11]);
+        assert!(matches!(trans.get_vertices(q), Err(Error::LimitExceeded { .. })));
+        let q = SpecificVertexQuery::single(id).outbound();
+        assert!(matches!(trans.get_edges(q), Err(Error::LimitExceeded { .. })));
+        let q = SpecificVertexQuery::single(id).outbound().limit(10);
+        assert!(trans.get_edges(q).unwrap().is_empty());
+        assert!(matches!(
+            trans.get_vertices_by_ids(&[id; 11]),
+            Err(Error::LimitExceeded { .. })
+        ));
+
+        // Methods that page through the datastore internally aren't limited
+        assert_eq!(trans.get_vertex_count_by_type(&Type::new("user").unwrap()).unwrap(), 1);
+        assert_eq!(trans.sample_vertices(None, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_enforce_the_same_limits_in_read_transactions() {
+        let datastore = datastore();
+        let t = Type::new("user").unwrap();
+        let id = datastore
+            .transaction()
+            .unwrap()
+            .create_vertex_from_type(t.clone())
+            .unwrap();
+        let trans = datastore.transaction().unwrap();
+        let read_trans = datastore.read_transaction().unwrap();
+
+        // Defaults that page through the datastore internally work the same
+        // in both, even though their pages are over the maximum limit
+        assert_eq!(
+            read_trans.get_vertex_types().unwrap(),
+            trans.get_vertex_types().unwrap()
+        );
+        assert_eq!(read_trans.get_edge_types().unwrap(), trans.get_edge_types().unwrap());
+        assert_eq!(read_trans.get_vertex_count_by_type(&t).unwrap(), 1);
+        assert_eq!(read_trans.sample_vertices(None, 10).unwrap().len(), 1);
+        assert!(read_trans.extract_subgraph(vec![id], 2, &[]).is_ok());
+
+        assert!(matches!(
+            read_trans.get_vertices(RangeVertexQuery::new()),
+            Err(Error::LimitExceeded { .. })
+        ));
+        assert!(matches!(
+            read_trans.sample_vertices(None, 11),
+            Err(Error::LimitExceeded { .. })
+        ));
+        assert!(matches!(
+            read_trans.extract_subgraph(vec![id], 3, &[]),
+            Err(Error::DepthExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn should_enforce_depth_limits() {
+        let datastore = datastore();
+        let trans = datastore.transaction().unwrap();
+        let id = trans.create_vertex_from_type(Type::new("user").unwrap()).unwrap();
+        let q = SpecificVertexQuery::single(id).outbound().limit(1).inbound().limit(1);
+        assert!(trans.get_vertices(q.clone()).unwrap().is_empty());
+
+        match trans.get_edges(q.outbound().limit(1)) {
+            Err(Error::DepthExceeded { depth: 3, max: 2 }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        assert!(trans.extract_subgraph(vec![id], 2, &[]).is_ok());
+        assert!(matches!(
+            trans.extract_subgraph(vec![id], 3, &[]),
+            Err(Error::DepthExceeded { .. })
+        ));
+        assert!(matches!(
+            trans.random_walk(id, 3, &[], None),
+            Err(Error::DepthExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn should_enforce_property_size_limits() {
+        let datastore = datastore();
+        let trans = datastore.transaction().unwrap();
+        let vertex = Vertex::new(Type::new("user").unwrap());
+        trans.create_vertex(&vertex).unwrap();
+        let q = SpecificVertexQuery::single(vertex.id).property("name");
+
+        trans
+            .set_vertex_properties(q.clone(), &JsonValue::from("short"))
+            .unwrap();
+
+        match trans.set_vertex_properties(q.clone(), &JsonValue::from("much too long")) {
+            Err(Error::PropertyTooLarge { size: 15, max: 8 }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        assert!(matches!(
+            trans.patch_vertex_properties(q, &JsonValue::from("much too long")),
+            Err(Error::PropertyTooLarge { .. })
+        ));
+
+        let items = vec![BulkInsertItem::VertexProperty(
+            vertex.id,
+            "name".to_string(),
+            JsonValue::from("much too long"),
+        )];
+        assert!(matches!(
+            datastore.bulk_insert(items.into_iter()),
+            Err(Error::PropertyTooLarge { .. })
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "test-suite"))]
+mod suite {
+    use super::{LimitedDatastore, Limits};
+    use crate::MemoryDatastore;
+
+    full_test_impl!(LimitedDatastore::new(MemoryDatastore::default(), Limits::new()));
+}
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
 
 use crate::errors::Result;
+use crate::util::merge_patch;
 use crate::{
     Datastore, Edge, EdgeDirection, EdgeKey, EdgeProperties, EdgeProperty, EdgePropertyQuery, EdgeQuery, NamedProperty,
     Transaction, Type, Vertex, VertexProperties, VertexProperty, VertexPropertyQuery, VertexQuery,
@@ -260,6 +261,11 @@ impl Transaction for MemoryTransaction {
         Ok(datastore.vertices.len() as u64)
     }
 
+    fn get_vertex_count_by_type(&self, t: &Type) -> Result<u64> {
+        let datastore = self.datastore.read().unwrap();
+        Ok(datastore.vertices.values().filter(|v| v == &t).count() as u64)
+    }
+
     fn create_edge(&self, key: &EdgeKey) -> Result<bool> {
         let mut datastore = self.datastore.write().unwrap();
 
@@ -365,6 +371,21 @@ impl Transaction for MemoryTransaction {
         Ok(())
     }
 
+    fn patch_vertex_properties(&self, q: VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        let mut datastore = self.datastore.write().unwrap();
+        let vertex_values: Vec<(Uuid, Type)> = datastore.get_vertex_values_by_query(q.inner)?.collect();
+
+        for (id, _) in vertex_values.into_iter() {
+            let value = datastore
+                .vertex_properties
+                .entry((id, q.name.clone()))
+                .or_insert(JsonValue::Null);
+            merge_patch(value, patch);
+        }
+
+        Ok(())
+    }
+
     fn delete_vertex_properties(&self, q: VertexPropertyQuery) -> Result<()> {
         let mut datastore = self.datastore.write().unwrap();
 
@@ -427,6 +448,21 @@ impl Transaction for MemoryTransaction {
         Ok(())
     }
 
+    fn patch_edge_properties(&self, q: EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        let mut datastore = self.datastore.write().unwrap();
+        let edge_values: Vec<(EdgeKey, DateTime<Utc>)> = datastore.get_edge_values_by_query(q.inner)?.collect();
+
+        for (key, _) in edge_values.into_iter() {
+            let value = datastore
+                .edge_properties
+                .entry((key, q.name.clone()))
+                .or_insert(JsonValue::Null);
+            merge_patch(value, patch);
+        }
+
+        Ok(())
+    }
+
     fn delete_edge_properties(&self, q: EdgePropertyQuery) -> Result<()> {
         let mut datastore = self.datastore.write().unwrap();
         let edge_values: Vec<(EdgeKey, DateTime<Utc>)> = datastore.get_edge_values_by_query(q.inner)?.collect();
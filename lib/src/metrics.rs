@@ -0,0 +1,565 @@
+//! Metrics instrumentation for datastores.
+//!
+//! Wrap any datastore in a `MeteredDatastore` to have every datastore and
+//! transaction call reported to a `MetricsSink`, along with how long it took,
+//! whether it failed, and how many rows it returned. Methods with default
+//! implementations are forwarded and measured as one operation each, rather
+//! than as the calls they make.
+//!
+//! `AggregateMetricsSink` is a ready-made sink that keeps running totals.
+//! With the `prometheus` feature enabled, it can also render them in the
+//! Prometheus text exposition format.
+
+use crate::errors::Result;
+use crate::models;
+use crate::traits::{Datastore, Transaction};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+#[cfg(feature = "prometheus")]
+use std::fmt::{Display, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// A single measured datastore or transaction call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    /// The name of the method that was called, e.g. `get_vertices`.
+    pub operation: &'static str,
+    /// How long the call took.
+    pub elapsed: Duration,
+    /// Whether the call returned an error.
+    pub failed: bool,
+    /// The number of rows returned, for calls that return a list.
+    pub rows: Option<u64>,
+}
+
+/// A destination for measurements.
+pub trait MetricsSink {
+    /// Records a measurement.
+    ///
+    /// # Arguments
+    /// * `measurement`: The measurement to record.
+    fn record(&self, measurement: Measurement);
+}
+
+/// Running totals for a single operation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OperationStats {
+    /// The number of calls.
+    pub calls: u64,
+    /// The number of calls that returned an error.
+    pub errors: u64,
+    /// The total number of rows returned.
+    pub rows: u64,
+    /// The total time spent in calls.
+    pub elapsed: Duration,
+}
+
+/// A metrics sink that keeps running totals per operation.
+#[derive(Debug, Default)]
+pub struct AggregateMetricsSink {
+    stats: Mutex<BTreeMap<&'static str, OperationStats>>,
+}
+
+impl AggregateMetricsSink {
+    /// Gets a copy of the totals recorded so far, keyed by operation name.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, OperationStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Renders the totals recorded so far in the Prometheus text exposition
+    /// format, so they can be served from a scrape endpoint.
+    #[cfg(feature = "prometheus")]
+    pub fn render_prometheus(&self) -> String {
+        let stats = self.stats.lock().unwrap();
+        let mut out = String::new();
+        render_family(
+            &mut out,
+            "indradb_operations_total",
+            "counter",
+            "Number of datastore operations.",
+        );
+        for (operation, s) in stats.iter() {
+            render_sample(&mut out, "indradb_operations_total", operation, s.calls);
+        }
+        render_family(
+            &mut out,
+            "indradb_operation_errors_total",
+            "counter",
+            "Number of failed datastore operations.",
+        );
+        for (operation, s) in stats.iter() {
+            render_sample(&mut out, "indradb_operation_errors_total", operation, s.errors);
+        }
+        render_family(
+            &mut out,
+            "indradb_operation_rows_total",
+            "counter",
+            "Number of rows returned.",
+        );
+        for (operation, s) in stats.iter() {
+            render_sample(&mut out, "indradb_operation_rows_total", operation, s.rows);
+        }
+        render_family(
+            &mut out,
+            "indradb_operation_duration_seconds",
+            "summary",
+            "Time spent in datastore operations.",
+        );
+        for (operation, s) in stats.iter() {
+            render_sample(
+                &mut out,
+                "indradb_operation_duration_seconds_sum",
+                operation,
+                s.elapsed.as_secs_f64(),
+            );
+            render_sample(&mut out, "indradb_operation_duration_seconds_count", operation, s.calls);
+        }
+        out
+    }
+}
+
+#[cfg(feature = "prometheus")]
+fn render_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+#[cfg(feature = "prometheus")]
+fn render_sample<V: Display>(out: &mut String, name: &str, operation: &str, value: V) {
+    writeln!(out, "{}{{operation=\"{}\"}} {}", name, operation, value).unwrap();
+}
+
+impl MetricsSink for AggregateMetricsSink {
+    fn record(&self, measurement: Measurement) {
+        let mut stats = self.stats.lock().unwrap();
+        let s = stats.entry(measurement.operation).or_default();
+        s.calls += 1;
+        s.elapsed += measurement.elapsed;
+
+        if measurement.failed {
+            s.errors += 1;
+        }
+
+        if let Some(rows) = measurement.rows {
+            s.rows += rows;
+        }
+    }
+}
+
+/// The number of rows in a call result, if the result is a list.
+trait RowCount {
+    fn row_count(&self) -> Option<u64>;
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+impl<K, V> RowCount for BTreeMap<K, V> {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+impl RowCount for models::Subgraph {
+    fn row_count(&self) -> Option<u64> {
+        Some((self.vertices.len() + self.edges.len()) as u64)
+    }
+}
+
+macro_rules! no_row_count {
+    ($($t:ty),*) => {
+        $(
+            impl RowCount for $t {
+                fn row_count(&self) -> Option<u64> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+no_row_count!((), bool, u64, f64, Uuid);
+
+fn measure<S, R, F>(sink: &S, operation: &'static str, f: F) -> Result<R>
+where
+    S: MetricsSink + ?Sized,
+    R: RowCount,
+    F: FnOnce() -> Result<R>,
+{
+    let start = Instant::now();
+    let result = f();
+    sink.record(Measurement {
+        operation,
+        elapsed: start.elapsed(),
+        failed: result.is_err(),
+        rows: result.as_ref().ok().and_then(RowCount::row_count),
+    });
+    result
+}
+
+/// A datastore that reports every call made through it, and through its
+/// transactions, to a metrics sink.
+#[derive(Debug)]
+pub struct MeteredDatastore<D: Datastore, S: MetricsSink> {
+    inner: D,
+    sink: Arc<S>,
+}
+
+impl<D: Datastore, S: MetricsSink> MeteredDatastore<D, S> {
+    /// Creates a new metered datastore.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    /// * `sink`: Where to send measurements.
+    pub fn new(inner: D, sink: Arc<S>) -> Self {
+        MeteredDatastore { inner, sink }
+    }
+
+    /// Gets the metrics sink.
+    pub fn sink(&self) -> &Arc<S> {
+        &self.sink
+    }
+}
+
+impl<D: Datastore, S: MetricsSink> Datastore for MeteredDatastore<D, S> {
+    type Trans = MeteredTransaction<D::Trans, S>;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        let start = Instant::now();
+        let result = self.inner.transaction();
+        self.sink.record(Measurement {
+            operation: "transaction",
+            elapsed: start.elapsed(),
+            failed: result.is_err(),
+            rows: None,
+        });
+        Ok(MeteredTransaction {
+            inner: result?,
+            sink: self.sink.clone(),
+        })
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        measure(&*self.sink, "bulk_insert", || self.inner.bulk_insert(items))
+    }
+
+    fn copy_into<O: Datastore>(&self, other: &O) -> Result<()> {
+        measure(&*self.sink, "copy_into", || self.inner.copy_into(other))
+    }
+
+    fn check_integrity(&self) -> Result<Vec<models::EdgeKey>> {
+        measure(&*self.sink, "check_integrity", || self.inner.check_integrity())
+    }
+
+    fn repair_integrity(&self, t: &models::Type) -> Result<Vec<models::EdgeKey>> {
+        measure(&*self.sink, "repair_integrity", || self.inner.repair_integrity(t))
+    }
+
+    fn diff<O: Datastore>(&self, other: &O) -> Result<Vec<models::Difference>> {
+        measure(&*self.sink, "diff", || self.inner.diff(other))
+    }
+}
+
+/// A transaction that reports every call made through it to a metrics sink.
+#[derive(Debug)]
+pub struct MeteredTransaction<T: Transaction, S: MetricsSink> {
+    inner: T,
+    sink: Arc<S>,
+}
+
+impl<T: Transaction, S: MetricsSink> Transaction for MeteredTransaction<T, S> {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        measure(&*self.sink, "create_vertex", || self.inner.create_vertex(vertex))
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        measure(&*self.sink, "create_vertex_from_type", || {
+            self.inner.create_vertex_from_type(t)
+        })
+    }
+
+    fn upsert_vertex_by_key(&self, key: &str, t: models::Type) -> Result<Uuid> {
+        measure(&*self.sink, "upsert_vertex_by_key", || {
+            self.inner.upsert_vertex_by_key(key, t)
+        })
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        measure(&*self.sink, "get_vertices", || self.inner.get_vertices(q))
+    }
+
+    fn get_vertices_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Option<models::Vertex>>> {
+        measure(&*self.sink, "get_vertices_by_ids", || {
+            self.inner.get_vertices_by_ids(ids)
+        })
+    }
+
+    fn sample_vertices(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Vertex>> {
+        measure(&*self.sink, "sample_vertices", || self.inner.sample_vertices(t, n))
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()> {
+        measure(&*self.sink, "delete_vertices", || self.inner.delete_vertices(q))
+    }
+
+    fn merge_vertices(&self, survivor: Uuid, duplicates: Vec<Uuid>, policy: models::MergePolicy) -> Result<bool> {
+        measure(&*self.sink, "merge_vertices", || {
+            self.inner.merge_vertices(survivor, duplicates, policy)
+        })
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        measure(&*self.sink, "get_vertex_count", || self.inner.get_vertex_count())
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        measure(&*self.sink, "get_vertex_count_by_type", || {
+            self.inner.get_vertex_count_by_type(t)
+        })
+    }
+
+    fn get_vertex_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        measure(&*self.sink, "get_vertex_types", || self.inner.get_vertex_types())
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        measure(&*self.sink, "create_edge", || self.inner.create_edge(key))
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        measure(&*self.sink, "get_edges", || self.inner.get_edges(q))
+    }
+
+    fn get_edges_by_keys(&self, keys: &[models::EdgeKey]) -> Result<Vec<Option<models::Edge>>> {
+        measure(&*self.sink, "get_edges_by_keys", || self.inner.get_edges_by_keys(keys))
+    }
+
+    fn sample_edges(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Edge>> {
+        measure(&*self.sink, "sample_edges", || self.inner.sample_edges(t, n))
+    }
+
+    fn get_edge_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        measure(&*self.sink, "get_edge_types", || self.inner.get_edge_types())
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()> {
+        measure(&*self.sink, "delete_edges", || self.inner.delete_edges(q))
+    }
+
+    fn extract_subgraph(&self, roots: Vec<Uuid>, depth: u32, edge_types: &[models::Type]) -> Result<models::Subgraph> {
+        measure(&*self.sink, "extract_subgraph", || {
+            self.inner.extract_subgraph(roots, depth, edge_types)
+        })
+    }
+
+    fn get_triangle_count(&self, id: Uuid, edge_types: &[models::Type]) -> Result<u64> {
+        measure(&*self.sink, "get_triangle_count", || {
+            self.inner.get_triangle_count(id, edge_types)
+        })
+    }
+
+    fn get_local_clustering_coefficient(&self, id: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        measure(&*self.sink, "get_local_clustering_coefficient", || {
+            self.inner.get_local_clustering_coefficient(id, edge_types)
+        })
+    }
+
+    fn get_common_neighbors(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<Vec<Uuid>> {
+        measure(&*self.sink, "get_common_neighbors", || {
+            self.inner.get_common_neighbors(a, b, edge_types)
+        })
+    }
+
+    fn get_jaccard_similarity(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        measure(&*self.sink, "get_jaccard_similarity", || {
+            self.inner.get_jaccard_similarity(a, b, edge_types)
+        })
+    }
+
+    fn get_adamic_adar_index(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        measure(&*self.sink, "get_adamic_adar_index", || {
+            self.inner.get_adamic_adar_index(a, b, edge_types)
+        })
+    }
+
+    fn random_walk(
+        &self,
+        start: Uuid,
+        length: u32,
+        edge_types: &[models::Type],
+        weight_property: Option<&str>,
+    ) -> Result<Vec<Uuid>> {
+        measure(&*self.sink, "random_walk", || {
+            self.inner.random_walk(start, length, edge_types, weight_property)
+        })
+    }
+
+    fn get_adjacent(
+        &self,
+        id: Uuid,
+        t: Option<&models::Type>,
+        limit: u32,
+    ) -> Result<Vec<(models::EdgeDirection, models::Edge)>> {
+        measure(&*self.sink, "get_adjacent", || self.inner.get_adjacent(id, t, limit))
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        measure(&*self.sink, "get_edge_count", || {
+            self.inner.get_edge_count(id, t, direction)
+        })
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        measure(&*self.sink, "get_vertex_properties", || {
+            self.inner.get_vertex_properties(q)
+        })
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        measure(&*self.sink, "find_vertices_with_property", || {
+            self.inner.find_vertices_with_property(q, predicate)
+        })
+    }
+
+    fn add_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        measure(&*self.sink, "add_vertex_label", || {
+            self.inner.add_vertex_label(id, label)
+        })
+    }
+
+    fn remove_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        measure(&*self.sink, "remove_vertex_label", || {
+            self.inner.remove_vertex_label(id, label)
+        })
+    }
+
+    fn get_vertices_with_label<Q: Into<models::VertexQuery>>(&self, q: Q, label: &str) -> Result<Vec<models::Vertex>> {
+        measure(&*self.sink, "get_vertices_with_label", || {
+            self.inner.get_vertices_with_label(q, label)
+        })
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        measure(&*self.sink, "get_all_vertex_properties", || {
+            self.inner.get_all_vertex_properties(q)
+        })
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()> {
+        measure(&*self.sink, "set_vertex_properties", || {
+            self.inner.set_vertex_properties(q, value)
+        })
+    }
+
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        measure(&*self.sink, "patch_vertex_properties", || {
+            self.inner.patch_vertex_properties(q, patch)
+        })
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<()> {
+        measure(&*self.sink, "delete_vertex_properties", || {
+            self.inner.delete_vertex_properties(q)
+        })
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        measure(&*self.sink, "get_edge_properties", || self.inner.get_edge_properties(q))
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        measure(&*self.sink, "get_all_edge_properties", || {
+            self.inner.get_all_edge_properties(q)
+        })
+    }
+
+    fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()> {
+        measure(&*self.sink, "set_edge_properties", || {
+            self.inner.set_edge_properties(q, value)
+        })
+    }
+
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        measure(&*self.sink, "patch_edge_properties", || {
+            self.inner.patch_edge_properties(q, patch)
+        })
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()> {
+        measure(&*self.sink, "delete_edge_properties", || {
+            self.inner.delete_edge_properties(q)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AggregateMetricsSink, MeteredDatastore};
+    use crate::{Datastore, MemoryDatastore, SpecificVertexQuery, Transaction, Type};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[test]
+    fn should_record_operations() {
+        let sink = Arc::new(AggregateMetricsSink::default());
+        let datastore = MeteredDatastore::new(MemoryDatastore::default(), sink.clone());
+        let trans = datastore.transaction().unwrap();
+        let t = Type::new("foo").unwrap();
+        let id = trans.create_vertex_from_type(t.clone()).unwrap();
+        trans.create_vertex_from_type(t).unwrap();
+        trans
+            .get_vertices(SpecificVertexQuery::new(vec![id, Uuid::default()]))
+            .unwrap();
+
+        let stats = sink.snapshot();
+        assert_eq!(stats["transaction"].calls, 1);
+        assert_eq!(stats["create_vertex_from_type"].calls, 2);
+        assert_eq!(stats["create_vertex_from_type"].errors, 0);
+        assert_eq!(stats["get_vertices"].calls, 1);
+        assert_eq!(stats["get_vertices"].rows, 1);
+        assert!(!stats.contains_key("get_edges"));
+    }
+
+    #[test]
+    fn should_record_default_methods_as_one_operation() {
+        let sink = Arc::new(AggregateMetricsSink::default());
+        let datastore = MeteredDatastore::new(MemoryDatastore::default(), sink.clone());
+        let trans = datastore.transaction().unwrap();
+        let id = trans.create_vertex_from_type(Type::new("foo").unwrap()).unwrap();
+        let subgraph = trans.extract_subgraph(vec![id], 1, &[]).unwrap();
+        assert_eq!(subgraph.vertices.len(), 1);
+        datastore.copy_into(&MemoryDatastore::default()).unwrap();
+
+        let stats = sink.snapshot();
+        assert_eq!(stats["extract_subgraph"].calls, 1);
+        assert_eq!(stats["extract_subgraph"].rows, 1);
+        assert_eq!(stats["copy_into"].calls, 1);
+        assert!(!stats.contains_key("get_vertices"));
+        assert!(!stats.contains_key("get_edges"));
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn should_render_prometheus() {
+        let sink = Arc::new(AggregateMetricsSink::default());
+        let datastore = MeteredDatastore::new(MemoryDatastore::default(), sink.clone());
+        datastore.transaction().unwrap().get_vertex_count().unwrap();
+        let rendered = sink.render_prometheus();
+        assert!(rendered.contains("# TYPE indradb_operations_total counter\n"));
+        assert!(rendered.contains("indradb_operations_total{operation=\"get_vertex_count\"} 1\n"));
+        assert!(rendered.contains("indradb_operation_errors_total{operation=\"transaction\"} 0\n"));
+        assert!(rendered.contains("indradb_operation_duration_seconds_count{operation=\"transaction\"} 1\n"));
+    }
+}
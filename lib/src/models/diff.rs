@@ -0,0 +1,37 @@
+use super::edges::EdgeKey;
+use super::vertices::Vertex;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+/// A difference between two graphs, going from the first to the second.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difference {
+    /// A vertex only exists in the second graph.
+    VertexAdded(Vertex),
+    /// A vertex only exists in the first graph.
+    VertexRemoved(Vertex),
+    /// A vertex exists in both graphs, with different types.
+    VertexChanged { old: Vertex, new: Vertex },
+    /// An edge only exists in the second graph.
+    EdgeAdded(EdgeKey),
+    /// An edge only exists in the first graph.
+    EdgeRemoved(EdgeKey),
+    /// A vertex property was added, removed or changed. `old` is `None` if
+    /// it only exists in the second graph, and `new` is `None` if it only
+    /// exists in the first.
+    VertexPropertyChanged {
+        id: Uuid,
+        name: String,
+        old: Option<JsonValue>,
+        new: Option<JsonValue>,
+    },
+    /// An edge property was added, removed or changed. `old` is `None` if
+    /// it only exists in the second graph, and `new` is `None` if it only
+    /// exists in the first.
+    EdgePropertyChanged {
+        key: EdgeKey,
+        name: String,
+        old: Option<JsonValue>,
+        new: Option<JsonValue>,
+    },
+}
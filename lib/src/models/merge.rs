@@ -0,0 +1,11 @@
+/// Decides which value is kept when vertices being merged have a property
+/// with the same name.
+#[derive(Eq, PartialEq, Clone, Debug, Hash, Copy)]
+pub enum MergePolicy {
+    /// Keep the surviving vertex's value. Between duplicates, the first
+    /// duplicate's value is kept.
+    KeepSurvivor,
+    /// Overwrite the surviving vertex's value. Between duplicates, the last
+    /// duplicate's value is kept.
+    KeepDuplicate,
+}
@@ -1,13 +1,23 @@
 mod bulk_insert;
+mod diff;
 mod edges;
+mod merge;
+mod predicates;
 mod properties;
 mod queries;
+mod subgraph;
 mod types;
 mod vertices;
 
 pub use self::bulk_insert::BulkInsertItem;
+pub use self::diff::Difference;
 pub use self::edges::{Edge, EdgeKey};
-pub use self::properties::{EdgeProperties, EdgeProperty, NamedProperty, VertexProperties, VertexProperty};
+pub use self::merge::MergePolicy;
+pub use self::predicates::PropertyPredicate;
+pub use self::properties::{
+    EdgeProperties, EdgeProperty, NamedProperty, VertexProperties, VertexProperty, LABELS_PROPERTY,
+};
 pub use self::queries::*;
+pub use self::subgraph::Subgraph;
 pub use self::types::Type;
 pub use self::vertices::Vertex;
@@ -0,0 +1,102 @@
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+
+/// A condition on a property value, used to find vertices by their
+/// properties.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyPredicate {
+    /// Matches values equal to the given one.
+    Eq(JsonValue),
+    /// Matches values not equal to the given one.
+    Ne(JsonValue),
+    /// Matches values less than the given one.
+    Lt(JsonValue),
+    /// Matches values less than or equal to the given one.
+    Lte(JsonValue),
+    /// Matches values greater than the given one.
+    Gt(JsonValue),
+    /// Matches values greater than or equal to the given one.
+    Gte(JsonValue),
+    /// Matches arrays that contain the given value, e.g. to store several
+    /// labels on a vertex as an array property and find vertices by label.
+    Contains(JsonValue),
+}
+
+impl PropertyPredicate {
+    /// Checks whether a value matches the predicate.
+    ///
+    /// Equality is JSON equality. Ordering comparisons only match numbers
+    /// against numbers and strings against strings (lexicographically); any
+    /// other pairing, e.g. a string against a number, doesn't match.
+    /// `Contains` only matches arrays.
+    ///
+    /// # Arguments
+    /// * `value` - The value to check.
+    pub fn matches(&self, value: &JsonValue) -> bool {
+        match self {
+            PropertyPredicate::Eq(other) => value == other,
+            PropertyPredicate::Ne(other) => value != other,
+            PropertyPredicate::Lt(other) => compare(value, other) == Some(Ordering::Less),
+            PropertyPredicate::Lte(other) => match compare(value, other) {
+                Some(ordering) => ordering != Ordering::Greater,
+                None => false,
+            },
+            PropertyPredicate::Gt(other) => compare(value, other) == Some(Ordering::Greater),
+            PropertyPredicate::Gte(other) => match compare(value, other) {
+                Some(ordering) => ordering != Ordering::Less,
+                None => false,
+            },
+            PropertyPredicate::Contains(other) => match value {
+                JsonValue::Array(values) => values.contains(other),
+                _ => false,
+            },
+        }
+    }
+}
+
+fn compare(value: &JsonValue, other: &JsonValue) -> Option<Ordering> {
+    match (value, other) {
+        (JsonValue::Number(value), JsonValue::Number(other)) => value.as_f64()?.partial_cmp(&other.as_f64()?),
+        (JsonValue::String(value), JsonValue::String(other)) => Some(value.cmp(other)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PropertyPredicate;
+    use serde_json::Value as JsonValue;
+
+    #[test]
+    fn should_compare_numbers() {
+        let value = JsonValue::from(0.95);
+        assert!(PropertyPredicate::Gt(JsonValue::from(0.9)).matches(&value));
+        assert!(PropertyPredicate::Gte(JsonValue::from(0.95)).matches(&value));
+        assert!(PropertyPredicate::Lt(JsonValue::from(1)).matches(&value));
+        assert!(!PropertyPredicate::Lte(JsonValue::from(0.5)).matches(&value));
+    }
+
+    #[test]
+    fn should_compare_strings() {
+        let value = JsonValue::from("b");
+        assert!(PropertyPredicate::Gt(JsonValue::from("a")).matches(&value));
+        assert!(PropertyPredicate::Eq(JsonValue::from("b")).matches(&value));
+        assert!(PropertyPredicate::Ne(JsonValue::from("c")).matches(&value));
+    }
+
+    #[test]
+    fn should_not_order_mismatched_types() {
+        let value = JsonValue::from("1");
+        assert!(!PropertyPredicate::Lt(JsonValue::from(2)).matches(&value));
+        assert!(!PropertyPredicate::Gte(JsonValue::from(0)).matches(&value));
+        assert!(!PropertyPredicate::Lte(JsonValue::Null).matches(&JsonValue::Null));
+    }
+
+    #[test]
+    fn should_check_array_contents() {
+        let value = JsonValue::from(vec!["person", "employee"]);
+        assert!(PropertyPredicate::Contains(JsonValue::from("employee")).matches(&value));
+        assert!(!PropertyPredicate::Contains(JsonValue::from("company")).matches(&value));
+        assert!(!PropertyPredicate::Contains(JsonValue::from("person")).matches(&JsonValue::from("person")));
+    }
+}
@@ -3,6 +3,10 @@ use crate::{Edge, Vertex};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+/// The name of the vertex property that holds a vertex's labels, as a JSON
+/// array of strings. See `Transaction::add_vertex_label`.
+pub const LABELS_PROPERTY: &str = "labels";
+
 /// Represents a vertex property.
 #[derive(Clone, Debug, PartialEq)]
 pub struct VertexProperty {
@@ -74,6 +74,8 @@ impl From<PipeVertexQuery> for VertexQuery {
     }
 }
 
+impl VertexQueryExt for VertexQuery {}
+
 /// Extension trait that specifies methods exposed by all vertex queries.
 pub trait VertexQueryExt: Into<VertexQuery> {
     /// Gets the outbound edges associated with the vertices.
@@ -302,6 +304,8 @@ impl From<PipeEdgeQuery> for EdgeQuery {
     }
 }
 
+impl EdgeQueryExt for EdgeQuery {}
+
 /// Extension trait that specifies methods exposed by all edge queries.
 pub trait EdgeQueryExt: Into<EdgeQuery> {
     /// Gets the vertices associated with the outbound end of the edges.
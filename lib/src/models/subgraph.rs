@@ -0,0 +1,25 @@
+use super::edges::Edge;
+use super::vertices::Vertex;
+
+/// A self-contained part of a graph: a set of vertices, and edges that only
+/// connect vertices in the set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Subgraph {
+    /// The vertices, ordered by id.
+    pub vertices: Vec<Vertex>,
+
+    /// The edges, ordered by key.
+    pub edges: Vec<Edge>,
+}
+
+impl Subgraph {
+    /// Creates a new subgraph.
+    ///
+    /// # Arguments
+    ///
+    /// * `vertices` - The vertices.
+    /// * `edges` - The edges.
+    pub fn new(vertices: Vec<Vertex>, edges: Vec<Edge>) -> Self {
+        Self { vertices, edges }
+    }
+}
@@ -5,7 +5,6 @@ use std::usize;
 
 use super::managers::*;
 use crate::errors::Result;
-use crate::util::next_uuid;
 use crate::{
     BulkInsertItem, Datastore, Edge, EdgeDirection, EdgeKey, EdgeProperties, EdgeProperty, EdgePropertyQuery,
     EdgeQuery, NamedProperty, Transaction, Type, Vertex, VertexProperties, VertexProperty, VertexPropertyQuery,
@@ -55,23 +54,10 @@ fn execute_vertex_query(db: &DB, q: VertexQuery) -> Result<Vec<VertexItem>> {
         VertexQuery::Range(q) => {
             let vertex_manager = VertexManager::new(db);
 
-            let next_uuid = match q.start_id {
-                Some(start_id) => {
-                    match next_uuid(start_id) {
-                        Ok(next_uuid) => next_uuid,
-                        // If we get an error back, it's because
-                        // `start_id` is the maximum possible value. We
-                        // know that no vertices exist whose ID is greater
-                        // than the maximum possible value, so just return
-                        // an empty list.
-                        Err(_) => return Ok(vec![]),
-                    }
-                }
-                None => Uuid::default(),
-            };
+            let start_id = q.start_id.unwrap_or_default();
 
             let mut iter: Box<dyn Iterator<Item = Result<VertexItem>>> =
-                Box::new(vertex_manager.iterate_for_range(next_uuid));
+                Box::new(vertex_manager.iterate_for_range(start_id));
 
             if let Some(ref t) = q.t {
                 iter = Box::new(iter.filter(move |item| match item {
@@ -0,0 +1,203 @@
+use crate::errors::{Error, Result};
+use crate::models;
+use crate::traits::Transaction;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A transaction wrapper that only allows reads. Any call that would mutate
+/// the datastore fails with `Error::ReadOnly` without reaching the
+/// underlying transaction. Reads, including the ones with default
+/// implementations, are passed straight through, so they behave exactly as
+/// they would on the underlying transaction.
+#[derive(Debug)]
+pub struct ReadOnlyTransaction<T: Transaction> {
+    inner: T,
+}
+
+impl<T: Transaction> ReadOnlyTransaction<T> {
+    /// Wraps a transaction so that it only allows reads.
+    ///
+    /// # Arguments
+    /// * `inner`: The transaction to wrap.
+    pub fn new(inner: T) -> Self {
+        ReadOnlyTransaction { inner }
+    }
+}
+
+impl<T: Transaction> Transaction for ReadOnlyTransaction<T> {
+    fn create_vertex(&self, _: &models::Vertex) -> Result<bool> {
+        Err(Error::ReadOnly)
+    }
+
+    fn create_vertex_from_type(&self, _: models::Type) -> Result<Uuid> {
+        Err(Error::ReadOnly)
+    }
+
+    fn upsert_vertex_by_key(&self, _: &str, _: models::Type) -> Result<Uuid> {
+        Err(Error::ReadOnly)
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        self.inner.get_vertices(q)
+    }
+
+    fn get_vertices_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Option<models::Vertex>>> {
+        self.inner.get_vertices_by_ids(ids)
+    }
+
+    fn sample_vertices(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Vertex>> {
+        self.inner.sample_vertices(t, n)
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, _: Q) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn merge_vertices(&self, _: Uuid, _: Vec<Uuid>, _: models::MergePolicy) -> Result<bool> {
+        Err(Error::ReadOnly)
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.inner.get_vertex_count()
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        self.inner.get_vertex_count_by_type(t)
+    }
+
+    fn get_vertex_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        self.inner.get_vertex_types()
+    }
+
+    fn create_edge(&self, _: &models::EdgeKey) -> Result<bool> {
+        Err(Error::ReadOnly)
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        self.inner.get_edges(q)
+    }
+
+    fn get_edges_by_keys(&self, keys: &[models::EdgeKey]) -> Result<Vec<Option<models::Edge>>> {
+        self.inner.get_edges_by_keys(keys)
+    }
+
+    fn sample_edges(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Edge>> {
+        self.inner.sample_edges(t, n)
+    }
+
+    fn get_edge_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        self.inner.get_edge_types()
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, _: Q) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn extract_subgraph(&self, roots: Vec<Uuid>, depth: u32, edge_types: &[models::Type]) -> Result<models::Subgraph> {
+        self.inner.extract_subgraph(roots, depth, edge_types)
+    }
+
+    fn get_triangle_count(&self, id: Uuid, edge_types: &[models::Type]) -> Result<u64> {
+        self.inner.get_triangle_count(id, edge_types)
+    }
+
+    fn get_local_clustering_coefficient(&self, id: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        self.inner.get_local_clustering_coefficient(id, edge_types)
+    }
+
+    fn get_common_neighbors(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<Vec<Uuid>> {
+        self.inner.get_common_neighbors(a, b, edge_types)
+    }
+
+    fn get_jaccard_similarity(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        self.inner.get_jaccard_similarity(a, b, edge_types)
+    }
+
+    fn get_adamic_adar_index(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        self.inner.get_adamic_adar_index(a, b, edge_types)
+    }
+
+    fn random_walk(
+        &self,
+        start: Uuid,
+        length: u32,
+        edge_types: &[models::Type],
+        weight_property: Option<&str>,
+    ) -> Result<Vec<Uuid>> {
+        self.inner.random_walk(start, length, edge_types, weight_property)
+    }
+
+    fn get_adjacent(
+        &self,
+        id: Uuid,
+        t: Option<&models::Type>,
+        limit: u32,
+    ) -> Result<Vec<(models::EdgeDirection, models::Edge)>> {
+        self.inner.get_adjacent(id, t, limit)
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        self.inner.get_edge_count(id, t, direction)
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        self.inner.get_vertex_properties(q)
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        self.inner.find_vertices_with_property(q, predicate)
+    }
+
+    fn add_vertex_label(&self, _: Uuid, _: &str) -> Result<bool> {
+        Err(Error::ReadOnly)
+    }
+
+    fn remove_vertex_label(&self, _: Uuid, _: &str) -> Result<bool> {
+        Err(Error::ReadOnly)
+    }
+
+    fn get_vertices_with_label<Q: Into<models::VertexQuery>>(&self, q: Q, label: &str) -> Result<Vec<models::Vertex>> {
+        self.inner.get_vertices_with_label(q, label)
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        self.inner.get_all_vertex_properties(q)
+    }
+
+    fn set_vertex_properties(&self, _: models::VertexPropertyQuery, _: &JsonValue) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn patch_vertex_properties(&self, _: models::VertexPropertyQuery, _: &JsonValue) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn delete_vertex_properties(&self, _: models::VertexPropertyQuery) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        self.inner.get_edge_properties(q)
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        self.inner.get_all_edge_properties(q)
+    }
+
+    fn set_edge_properties(&self, _: models::EdgePropertyQuery, _: &JsonValue) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn patch_edge_properties(&self, _: models::EdgePropertyQuery, _: &JsonValue) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+
+    fn delete_edge_properties(&self, _: models::EdgePropertyQuery) -> Result<()> {
+        Err(Error::ReadOnly)
+    }
+}
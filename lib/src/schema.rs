@@ -0,0 +1,891 @@
+use crate::errors::{Error, Result};
+use crate::models;
+use crate::models::VertexQueryExt;
+use crate::traits::{apply_merge, plan_merge, Datastore, MergePlan, Transaction};
+use crate::util::merge_patch;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// The vertex and edge types a `SchemaDatastore` accepts, the vertex
+/// properties whose values must be unique, and the vertex properties that
+/// must be set.
+///
+/// Edge types are declared along with the types of the vertices they
+/// connect, so e.g. `follows` can be allowed from a `user` to a `user`
+/// without also being allowed from a `user` to a `venue`.
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    vertex_types: HashSet<models::Type>,
+    edge_types: HashSet<(models::Type, models::Type, models::Type)>,
+    unique_properties: HashSet<(models::Type, String)>,
+    required_properties: HashSet<(models::Type, String)>,
+}
+
+impl Schema {
+    /// Creates a new, empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows a vertex type.
+    ///
+    /// # Arguments
+    /// * `t` - The vertex type.
+    pub fn vertex_type(mut self, t: models::Type) -> Self {
+        self.vertex_types.insert(t);
+        self
+    }
+
+    /// Allows an edge type between vertices of the given types.
+    ///
+    /// # Arguments
+    /// * `outbound_t` - The type of the outbound vertex.
+    /// * `t` - The edge type.
+    /// * `inbound_t` - The type of the inbound vertex.
+    pub fn edge_type(mut self, outbound_t: models::Type, t: models::Type, inbound_t: models::Type) -> Self {
+        self.edge_types.insert((outbound_t, t, inbound_t));
+        self
+    }
+
+    /// Requires that no two vertices of a type have the same value for a
+    /// property.
+    ///
+    /// # Arguments
+    /// * `t` - The vertex type.
+    /// * `name` - The property name.
+    pub fn unique_property<S: Into<String>>(mut self, t: models::Type, name: S) -> Self {
+        self.unique_properties.insert((t, name.into()));
+        self
+    }
+
+    /// Requires that every vertex of a type has a property. Since the
+    /// property has to be set when the vertex is created, vertices of the
+    /// type can only be created through `bulk_insert`, in the same batch as
+    /// their required properties. The property can't be deleted afterwards.
+    ///
+    /// # Arguments
+    /// * `t` - The vertex type.
+    /// * `name` - The property name.
+    pub fn required_property<S: Into<String>>(mut self, t: models::Type, name: S) -> Self {
+        self.required_properties.insert((t, name.into()));
+        self
+    }
+
+    fn is_unique_property(&self, t: &models::Type, name: &str) -> bool {
+        self.unique_properties.contains(&(t.clone(), name.to_string()))
+    }
+
+    fn has_unique_property(&self, name: &str) -> bool {
+        self.unique_properties.iter().any(|(_, n)| n == name)
+    }
+
+    fn is_required_property(&self, t: &models::Type, name: &str) -> bool {
+        self.required_properties.contains(&(t.clone(), name.to_string()))
+    }
+
+    fn has_required_property(&self, name: &str) -> bool {
+        self.required_properties.iter().any(|(_, n)| n == name)
+    }
+
+    fn get_required_properties(&self, t: &models::Type) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .required_properties
+            .iter()
+            .filter(|(required_t, _)| required_t == t)
+            .map(|(_, name)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    // Validates a vertex being created on its own, which can't have any
+    // properties yet.
+    fn validate_vertex_creation(&self, t: &models::Type) -> Result<()> {
+        self.validate_vertex_type(t)?;
+
+        match self.get_required_properties(t).first() {
+            Some(name) => Err(Error::SchemaViolation {
+                message: format!(
+                    "vertex type `{}` requires property `{}`, so its vertices must be bulk inserted with their properties",
+                    t.0, name
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_vertex_type(&self, t: &models::Type) -> Result<()> {
+        if self.vertex_types.contains(t) {
+            Ok(())
+        } else {
+            Err(Error::SchemaViolation {
+                message: format!("vertex type `{}` is not declared", t.0),
+            })
+        }
+    }
+
+    fn validate_edge(&self, outbound_t: &models::Type, t: &models::Type, inbound_t: &models::Type) -> Result<()> {
+        if self
+            .edge_types
+            .contains(&(outbound_t.clone(), t.clone(), inbound_t.clone()))
+        {
+            Ok(())
+        } else {
+            Err(Error::SchemaViolation {
+                message: format!(
+                    "edge type `{}` is not declared from `{}` to `{}`",
+                    t.0, outbound_t.0, inbound_t.0
+                ),
+            })
+        }
+    }
+}
+
+fn get_vertex_types<T: Transaction>(trans: &T, ids: Vec<Uuid>) -> Result<HashMap<Uuid, models::Type>> {
+    Ok(trans
+        .get_vertices(models::SpecificVertexQuery::new(ids))?
+        .into_iter()
+        .map(|v| (v.id, v.t))
+        .collect())
+}
+
+// Checks that setting a property to the given values wouldn't give two
+// vertices of a type with a unique constraint on it the same value, either
+// among the values themselves or against what's already stored.
+fn validate_unique<T: Transaction>(
+    schema: &Schema,
+    trans: &T,
+    name: &str,
+    values: &[(models::Vertex, JsonValue)],
+) -> Result<()> {
+    for (i, (vertex, value)) in values.iter().enumerate() {
+        if !schema.is_unique_property(&vertex.t, name) {
+            continue;
+        }
+
+        let conflicts_in_batch = values[..i]
+            .iter()
+            .any(|(other, other_value)| other.id != vertex.id && other.t == vertex.t && other_value == value);
+
+        let conflicts_in_datastore = || -> Result<bool> {
+            let q = models::RangeVertexQuery::new().t(vertex.t.clone()).property(name);
+            let predicate = models::PropertyPredicate::Eq(value.clone());
+            let existing = trans.find_vertices_with_property(q, &predicate)?;
+            Ok(existing.iter().any(|other| other.id != vertex.id))
+        };
+
+        if conflicts_in_batch || conflicts_in_datastore()? {
+            return Err(Error::UniquenessViolation {
+                message: format!(
+                    "another vertex of type `{}` already has this value for `{}`",
+                    vertex.t.0, name
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// A datastore that only allows vertices and edges of types declared in a
+/// schema. Creating anything else fails with `Error::SchemaViolation`.
+///
+/// Edges are checked against the types of the vertices they connect, which
+/// are looked up at write time. Bulk inserts are checked in full before
+/// anything is inserted; unlike regular bulk inserts, they can't create
+/// edges to vertices that don't exist yet, since their types are unknown.
+///
+/// Vertices of types with required properties can only be created by bulk
+/// inserts that include those properties, and the properties can't be
+/// deleted.
+///
+/// Merging vertices checks the edges and unique values that would move to
+/// the survivor before deleting the duplicates, so a merge the schema
+/// rejects leaves everything in place.
+///
+/// Writes to unique properties fail with `Error::UniquenessViolation` if
+/// they would give two vertices of the same type the same value. Checking
+/// scans the existing values, and is only race-free if every write to the
+/// underlying datastore goes through this one.
+#[derive(Debug)]
+pub struct SchemaDatastore<D: Datastore> {
+    inner: D,
+    schema: Arc<Schema>,
+    unique_lock: Arc<Mutex<()>>,
+}
+
+impl<D: Datastore> SchemaDatastore<D> {
+    /// Creates a new schema-checked datastore.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    /// * `schema`: The types to allow.
+    pub fn new(inner: D, schema: Schema) -> Self {
+        SchemaDatastore {
+            inner,
+            schema: Arc::new(schema),
+            unique_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Gets the schema.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+impl<D: Datastore> Datastore for SchemaDatastore<D> {
+    type Trans = SchemaTransaction<D::Trans>;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        Ok(SchemaTransaction {
+            inner: self.inner.transaction()?,
+            schema: self.schema.clone(),
+            unique_lock: self.unique_lock.clone(),
+        })
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let items: Vec<models::BulkInsertItem> = items.collect();
+        let mut vertex_types = HashMap::new();
+        let mut missing_ids = Vec::new();
+
+        let inserted_props: HashSet<(Uuid, &str)> = items
+            .iter()
+            .filter_map(|item| match item {
+                models::BulkInsertItem::VertexProperty(id, ref name, _) => Some((*id, name.as_str())),
+                _ => None,
+            })
+            .collect();
+
+        for item in &items {
+            if let models::BulkInsertItem::Vertex(ref vertex) = item {
+                self.schema.validate_vertex_type(&vertex.t)?;
+
+                for name in self.schema.get_required_properties(&vertex.t) {
+                    if !inserted_props.contains(&(vertex.id, name)) {
+                        return Err(Error::SchemaViolation {
+                            message: format!(
+                                "vertex of type `{}` is missing required property `{}`",
+                                vertex.t.0, name
+                            ),
+                        });
+                    }
+                }
+
+                vertex_types.insert(vertex.id, vertex.t.clone());
+            }
+        }
+
+        for item in &items {
+            match item {
+                models::BulkInsertItem::Edge(ref key) => {
+                    for id in &[key.outbound_id, key.inbound_id] {
+                        if !vertex_types.contains_key(id) {
+                            missing_ids.push(*id);
+                        }
+                    }
+                }
+                models::BulkInsertItem::VertexProperty(id, ref name, _)
+                    if self.schema.has_unique_property(name) && !vertex_types.contains_key(id) =>
+                {
+                    missing_ids.push(*id);
+                }
+                _ => {}
+            }
+        }
+
+        let trans = self.inner.transaction()?;
+
+        if !missing_ids.is_empty() {
+            vertex_types.extend(get_vertex_types(&trans, missing_ids)?);
+        }
+
+        for item in &items {
+            if let models::BulkInsertItem::Edge(ref key) = item {
+                match (vertex_types.get(&key.outbound_id), vertex_types.get(&key.inbound_id)) {
+                    (Some(outbound_t), Some(inbound_t)) => self.schema.validate_edge(outbound_t, &key.t, inbound_t)?,
+                    _ => {
+                        return Err(Error::SchemaViolation {
+                            message: format!("edge type `{}` connects a vertex that does not exist", key.t.0),
+                        })
+                    }
+                }
+            }
+        }
+
+        let mut unique_values: HashMap<&str, Vec<(models::Vertex, JsonValue)>> = HashMap::new();
+
+        for item in &items {
+            if let models::BulkInsertItem::VertexProperty(id, ref name, ref value) = item {
+                if let Some(t) = vertex_types.get(id) {
+                    if self.schema.is_unique_property(t, name) {
+                        let vertex = models::Vertex::with_id(*id, t.clone());
+                        unique_values.entry(name).or_default().push((vertex, value.clone()));
+                    }
+                }
+            }
+        }
+
+        // Hold the lock even if there is nothing to check, so that a
+        // concurrent unique write can't slip in between the checks and the
+        // insert
+        let _guard = self.unique_lock.lock().unwrap();
+
+        for (name, values) in &unique_values {
+            validate_unique(&self.schema, &trans, name, values)?;
+        }
+
+        self.inner.bulk_insert(items.into_iter())
+    }
+}
+
+/// A transaction that checks writes against a `SchemaDatastore`'s schema.
+#[derive(Debug)]
+pub struct SchemaTransaction<T: Transaction> {
+    inner: T,
+    schema: Arc<Schema>,
+    unique_lock: Arc<Mutex<()>>,
+}
+
+impl<T: Transaction> SchemaTransaction<T> {
+    // Gets the distinct vertices matching a property query.
+    fn get_distinct_vertices(&self, q: &models::VertexPropertyQuery) -> Result<Vec<models::Vertex>> {
+        let mut seen = HashSet::new();
+        let mut vertices = self.inner.get_vertices(q.inner.clone())?;
+        vertices.retain(|v| seen.insert(v.id));
+        Ok(vertices)
+    }
+
+    // Checks that every edge and vertex property a merge would write to the
+    // survivor is allowed, before the merge deletes anything.
+    fn validate_merge(&self, survivor: Uuid, plan: &MergePlan, policy: models::MergePolicy) -> Result<()> {
+        let ids: Vec<Uuid> = plan
+            .edges
+            .iter()
+            .flat_map(|(key, _)| vec![key.outbound_id, key.inbound_id])
+            .chain(std::iter::once(survivor))
+            .collect();
+        let vertex_types = get_vertex_types(&self.inner, ids)?;
+
+        for (key, _) in &plan.edges {
+            if let (Some(outbound_t), Some(inbound_t)) =
+                (vertex_types.get(&key.outbound_id), vertex_types.get(&key.inbound_id))
+            {
+                self.schema.validate_edge(outbound_t, &key.t, inbound_t)?;
+            }
+        }
+
+        let survivor_t = &vertex_types[&survivor];
+        let mut values: HashMap<&str, JsonValue> = HashMap::new();
+
+        for prop in &plan.vertex_props {
+            if !self.schema.is_unique_property(survivor_t, &prop.name) {
+                continue;
+            }
+
+            if policy == models::MergePolicy::KeepSurvivor && values.contains_key(prop.name.as_str()) {
+                continue;
+            }
+
+            let q = models::SpecificVertexQuery::single(survivor).property(prop.name.clone());
+
+            if policy == models::MergePolicy::KeepSurvivor && !self.inner.get_vertex_properties(q)?.is_empty() {
+                continue;
+            }
+
+            values.insert(&prop.name, prop.value.clone());
+        }
+
+        for (name, value) in values {
+            // The duplicates are deleted before the value is written, so
+            // they can hold it already
+            let q = models::RangeVertexQuery::new().t(survivor_t.clone()).property(name);
+            let predicate = models::PropertyPredicate::Eq(value);
+            let conflicts = self
+                .inner
+                .find_vertices_with_property(q, &predicate)?
+                .into_iter()
+                .any(|other| other.id != survivor && !plan.duplicates.contains(&other.id));
+
+            if conflicts {
+                return Err(Error::UniquenessViolation {
+                    message: format!(
+                        "another vertex of type `{}` already has this value for `{}`",
+                        survivor_t.0, name
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Transaction> Transaction for SchemaTransaction<T> {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        self.schema.validate_vertex_creation(&vertex.t)?;
+        self.inner.create_vertex(vertex)
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        self.schema.validate_vertex_creation(&t)?;
+        self.inner.create_vertex_from_type(t)
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        self.inner.get_vertices(q)
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()> {
+        self.inner.delete_vertices(q)
+    }
+
+    fn merge_vertices(&self, survivor: Uuid, duplicates: Vec<Uuid>, policy: models::MergePolicy) -> Result<bool> {
+        let _guard = self.unique_lock.lock().unwrap();
+
+        match plan_merge(&self.inner, survivor, duplicates)? {
+            Some(plan) => {
+                self.validate_merge(survivor, &plan, policy)?;
+                // Everything was checked above, so write to the inner
+                // transaction directly
+                apply_merge(&self.inner, survivor, plan, policy)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.inner.get_vertex_count()
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        self.inner.get_vertex_count_by_type(t)
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let vertex_types = get_vertex_types(&self.inner, vec![key.outbound_id, key.inbound_id])?;
+
+        match (vertex_types.get(&key.outbound_id), vertex_types.get(&key.inbound_id)) {
+            (Some(outbound_t), Some(inbound_t)) => {
+                self.schema.validate_edge(outbound_t, &key.t, inbound_t)?;
+                self.inner.create_edge(key)
+            }
+            // One of the vertices is missing, so the edge can't be created
+            // regardless of its type
+            _ => Ok(false),
+        }
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        self.inner.get_edges(q)
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()> {
+        self.inner.delete_edges(q)
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        self.inner.get_edge_count(id, t, direction)
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        self.inner.get_vertex_properties(q)
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        self.inner.find_vertices_with_property(q, predicate)
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        self.inner.get_all_vertex_properties(q)
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()> {
+        if self.schema.has_unique_property(&q.name) {
+            let _guard = self.unique_lock.lock().unwrap();
+            let vertices = self.get_distinct_vertices(&q)?;
+            let values: Vec<(models::Vertex, JsonValue)> = vertices.into_iter().map(|v| (v, value.clone())).collect();
+            validate_unique(&self.schema, &self.inner, &q.name, &values)?;
+
+            // Write to exactly the vertices that were checked
+            let ids = values.into_iter().map(|(v, _)| v.id).collect();
+            let q = models::SpecificVertexQuery::new(ids).property(q.name);
+            return self.inner.set_vertex_properties(q, value);
+        }
+
+        self.inner.set_vertex_properties(q, value)
+    }
+
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        if self.schema.has_unique_property(&q.name) {
+            let _guard = self.unique_lock.lock().unwrap();
+            let vertices = self.get_distinct_vertices(&q)?;
+            let ids = vertices.iter().map(|v| v.id).collect();
+            let mut current: HashMap<Uuid, JsonValue> = self
+                .inner
+                .get_vertex_properties(models::SpecificVertexQuery::new(ids).property(q.name.clone()))?
+                .into_iter()
+                .map(|p| (p.id, p.value))
+                .collect();
+
+            let values: Vec<(models::Vertex, JsonValue)> = vertices
+                .into_iter()
+                .map(|v| {
+                    let mut value = current.remove(&v.id).unwrap_or(JsonValue::Null);
+                    merge_patch(&mut value, patch);
+                    (v, value)
+                })
+                .collect();
+
+            validate_unique(&self.schema, &self.inner, &q.name, &values)?;
+
+            for (vertex, value) in values {
+                let q = models::SpecificVertexQuery::single(vertex.id).property(q.name.clone());
+                self.inner.set_vertex_properties(q, &value)?;
+            }
+
+            return Ok(());
+        }
+
+        self.inner.patch_vertex_properties(q, patch)
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<()> {
+        if self.schema.has_required_property(&q.name) {
+            for vertex in self.get_distinct_vertices(&q)? {
+                if self.schema.is_required_property(&vertex.t, &q.name) {
+                    return Err(Error::SchemaViolation {
+                        message: format!("property `{}` is required on vertex type `{}`", q.name, vertex.t.0),
+                    });
+                }
+            }
+        }
+
+        self.inner.delete_vertex_properties(q)
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        self.inner.get_edge_properties(q)
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        self.inner.get_all_edge_properties(q)
+    }
+
+    fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()> {
+        self.inner.set_edge_properties(q, value)
+    }
+
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        self.inner.patch_edge_properties(q, patch)
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()> {
+        self.inner.delete_edge_properties(q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Schema, SchemaDatastore};
+    use crate::{
+        BulkInsertItem, Datastore, EdgeDirection, EdgeKey, Error, MemoryDatastore, MergePolicy, SpecificVertexQuery,
+        Transaction, Type, Vertex, VertexQueryExt,
+    };
+    use serde_json::Value as JsonValue;
+
+    fn datastore() -> SchemaDatastore<MemoryDatastore> {
+        let user_t = Type::new("user").unwrap();
+        let venue_t = Type::new("venue").unwrap();
+        let schema = Schema::new()
+            .vertex_type(user_t.clone())
+            .vertex_type(venue_t.clone())
+            .edge_type(user_t.clone(), Type::new("follows").unwrap(), user_t.clone())
+            .edge_type(user_t.clone(), Type::new("visited").unwrap(), venue_t)
+            .unique_property(user_t, "email")
+            .vertex_type(Type::new("city").unwrap())
+            .required_property(Type::new("city").unwrap(), "name");
+        SchemaDatastore::new(MemoryDatastore::default(), schema)
+    }
+
+    #[test]
+    fn should_validate_vertex_types() {
+        let trans = datastore().transaction().unwrap();
+        trans.create_vertex_from_type(Type::new("user").unwrap()).unwrap();
+
+        match trans.create_vertex(&Vertex::new(Type::new("usr").unwrap())) {
+            Err(Error::SchemaViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(trans.get_vertex_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn should_validate_edge_types() {
+        let trans = datastore().transaction().unwrap();
+        let user_id = trans.create_vertex_from_type(Type::new("user").unwrap()).unwrap();
+        let other_user_id = trans.create_vertex_from_type(Type::new("user").unwrap()).unwrap();
+        let venue_id = trans.create_vertex_from_type(Type::new("venue").unwrap()).unwrap();
+        let follows_t = Type::new("follows").unwrap();
+
+        assert!(trans
+            .create_edge(&EdgeKey::new(user_id, follows_t.clone(), other_user_id))
+            .unwrap());
+        assert!(trans
+            .create_edge(&EdgeKey::new(user_id, Type::new("visited").unwrap(), venue_id))
+            .unwrap());
+
+        for key in &[
+            EdgeKey::new(user_id, Type::new("folows").unwrap(), other_user_id),
+            EdgeKey::new(user_id, follows_t.clone(), venue_id),
+        ] {
+            match trans.create_edge(key) {
+                Err(Error::SchemaViolation { .. }) => (),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        assert!(!trans
+            .create_edge(&EdgeKey::new(
+                user_id,
+                follows_t,
+                Vertex::new(Type::new("user").unwrap()).id
+            ))
+            .unwrap());
+    }
+
+    #[test]
+    fn should_validate_bulk_inserts() {
+        let datastore = datastore();
+        let user = Vertex::new(Type::new("user").unwrap());
+        let venue = Vertex::new(Type::new("venue").unwrap());
+        let key = EdgeKey::new(user.id, Type::new("visited").unwrap(), venue.id);
+        let items = vec![
+            BulkInsertItem::Vertex(user.clone()),
+            BulkInsertItem::Vertex(venue.clone()),
+            BulkInsertItem::Edge(key),
+        ];
+        datastore.bulk_insert(items.into_iter()).unwrap();
+
+        // Edges can connect to vertices inserted earlier
+        let other_user = Vertex::new(Type::new("user").unwrap());
+        let key = EdgeKey::new(other_user.id, Type::new("follows").unwrap(), user.id);
+        let items = vec![BulkInsertItem::Vertex(other_user), BulkInsertItem::Edge(key)];
+        datastore.bulk_insert(items.into_iter()).unwrap();
+
+        // Nothing in a batch is inserted if any of it is invalid
+        let key = EdgeKey::new(venue.id, Type::new("visited").unwrap(), user.id);
+        let items = vec![
+            BulkInsertItem::Vertex(Vertex::new(Type::new("user").unwrap())),
+            BulkInsertItem::Edge(key),
+        ];
+
+        match datastore.bulk_insert(items.into_iter()) {
+            Err(Error::SchemaViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let trans = datastore.transaction().unwrap();
+        assert_eq!(trans.get_vertex_count().unwrap(), 3);
+    }
+
+    #[test]
+    fn should_enforce_unique_properties() {
+        let datastore = datastore();
+        let trans = datastore.transaction().unwrap();
+        let user_t = Type::new("user").unwrap();
+        let first_id = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let second_id = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let venue_id = trans.create_vertex_from_type(Type::new("venue").unwrap()).unwrap();
+        let email = JsonValue::from("a@example.com");
+
+        let q = SpecificVertexQuery::single(first_id).property("email");
+        trans.set_vertex_properties(q.clone(), &email).unwrap();
+        // Setting the same value again on the same vertex is fine
+        trans.set_vertex_properties(q, &email).unwrap();
+        // The constraint only applies to users
+        let q = SpecificVertexQuery::single(venue_id).property("email");
+        trans.set_vertex_properties(q, &email).unwrap();
+
+        let q = SpecificVertexQuery::single(second_id).property("email");
+
+        match trans.set_vertex_properties(q.clone(), &email) {
+            Err(Error::UniquenessViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match trans.patch_vertex_properties(q, &email) {
+            Err(Error::UniquenessViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Two vertices can't be given the same value at once
+        let q = SpecificVertexQuery::new(vec![first_id, second_id]).property("email");
+
+        match trans.set_vertex_properties(q, &JsonValue::from("b@example.com")) {
+            Err(Error::UniquenessViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let item = BulkInsertItem::VertexProperty(second_id, "email".to_string(), email);
+
+        match datastore.bulk_insert(vec![item].into_iter()) {
+            Err(Error::UniquenessViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let q = SpecificVertexQuery::single(second_id).property("email");
+        assert_eq!(trans.get_vertex_properties(q).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn should_merge_vertices_with_unique_properties() {
+        let trans = datastore().transaction().unwrap();
+        let user_t = Type::new("user").unwrap();
+        let survivor_id = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let duplicate_id = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let other_id = trans.create_vertex_from_type(user_t).unwrap();
+        let follows_t = Type::new("follows").unwrap();
+        trans
+            .create_edge(&EdgeKey::new(duplicate_id, follows_t.clone(), other_id))
+            .unwrap();
+        let email = JsonValue::from("a@example.com");
+        trans
+            .set_vertex_properties(SpecificVertexQuery::single(duplicate_id).property("email"), &email)
+            .unwrap();
+
+        assert!(trans
+            .merge_vertices(survivor_id, vec![duplicate_id], MergePolicy::KeepSurvivor)
+            .unwrap());
+
+        let props = trans
+            .get_vertex_properties(SpecificVertexQuery::single(survivor_id).property("email"))
+            .unwrap();
+        assert_eq!(props.len(), 1);
+        assert_eq!(props[0].value, email);
+        assert_eq!(trans.get_vertex_count().unwrap(), 2);
+        assert_eq!(
+            trans
+                .get_edge_count(survivor_id, Some(&follows_t), EdgeDirection::Outbound)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn should_leave_duplicates_in_place_when_a_merge_is_rejected() {
+        let trans = datastore().transaction().unwrap();
+        let user_t = Type::new("user").unwrap();
+        let venue_t = Type::new("venue").unwrap();
+        let follows_t = Type::new("follows").unwrap();
+        let user_id = trans.create_vertex_from_type(user_t.clone()).unwrap();
+        let other_user_id = trans.create_vertex_from_type(user_t).unwrap();
+        let venue_id = trans.create_vertex_from_type(venue_t.clone()).unwrap();
+        let other_venue_id = trans.create_vertex_from_type(venue_t).unwrap();
+
+        // `follows` isn't declared from a venue, so the duplicate's edge
+        // can't move to the survivor
+        trans
+            .create_edge(&EdgeKey::new(user_id, follows_t.clone(), other_user_id))
+            .unwrap();
+
+        match trans.merge_vertices(venue_id, vec![user_id], MergePolicy::KeepSurvivor) {
+            Err(Error::SchemaViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(trans.get_vertex_count().unwrap(), 4);
+        assert_eq!(
+            trans
+                .get_edge_count(user_id, Some(&follows_t), EdgeDirection::Outbound)
+                .unwrap(),
+            1
+        );
+
+        // Emails are only unique among users, so a venue can share one, but
+        // can't bring it into a user
+        let email = JsonValue::from("a@example.com");
+        trans
+            .set_vertex_properties(SpecificVertexQuery::single(other_user_id).property("email"), &email)
+            .unwrap();
+        trans
+            .set_vertex_properties(SpecificVertexQuery::single(other_venue_id).property("email"), &email)
+            .unwrap();
+
+        match trans.merge_vertices(user_id, vec![other_venue_id], MergePolicy::KeepSurvivor) {
+            Err(Error::UniquenessViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(trans.get_vertex_count().unwrap(), 4);
+        let props = trans
+            .get_vertex_properties(SpecificVertexQuery::single(other_venue_id).property("email"))
+            .unwrap();
+        assert_eq!(props.len(), 1);
+        assert_eq!(props[0].value, email);
+    }
+
+    #[test]
+    fn should_enforce_required_properties() {
+        let datastore = datastore();
+        let trans = datastore.transaction().unwrap();
+        let city_t = Type::new("city").unwrap();
+
+        match trans.create_vertex_from_type(city_t.clone()) {
+            Err(Error::SchemaViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let city = Vertex::new(city_t);
+        let items = vec![BulkInsertItem::Vertex(city.clone())];
+
+        match datastore.bulk_insert(items.into_iter()) {
+            Err(Error::SchemaViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(trans.get_vertex_count().unwrap(), 0);
+
+        let items = vec![
+            BulkInsertItem::Vertex(city.clone()),
+            BulkInsertItem::VertexProperty(city.id, "name".to_string(), JsonValue::from("Oslo")),
+        ];
+        datastore.bulk_insert(items.into_iter()).unwrap();
+        assert_eq!(trans.get_vertex_count().unwrap(), 1);
+
+        let q = SpecificVertexQuery::single(city.id).property("name");
+        trans
+            .set_vertex_properties(q.clone(), &JsonValue::from("Bergen"))
+            .unwrap();
+
+        match trans.delete_vertex_properties(q.clone()) {
+            Err(Error::SchemaViolation { .. }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(
+            trans.get_vertex_properties(q).unwrap()[0].value,
+            JsonValue::from("Bergen")
+        );
+
+        // Other types' properties with the same name can still be deleted
+        let user_id = trans.create_vertex_from_type(Type::new("user").unwrap()).unwrap();
+        let q = SpecificVertexQuery::single(user_id).property("name");
+        trans
+            .set_vertex_properties(q.clone(), &JsonValue::from("alice"))
+            .unwrap();
+        trans.delete_vertex_properties(q).unwrap();
+    }
+}
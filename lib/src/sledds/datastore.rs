@@ -9,7 +9,6 @@ use super::managers::*;
 use crate::errors::Result;
 use crate::models;
 use crate::models::*;
-use crate::util::next_uuid;
 
 use chrono::offset::Utc;
 use serde_json::Value as JsonValue;
@@ -156,23 +155,10 @@ impl SledTransaction {
             VertexQuery::Range(q) => {
                 let vertex_manager = VertexManager::new(&self.holder);
 
-                let next_uuid = match q.start_id {
-                    Some(start_id) => {
-                        match next_uuid(start_id) {
-                            Ok(next_uuid) => next_uuid,
-                            // If we get an error back, it's because
-                            // `start_id` is the maximum possible value. We
-                            // know that no vertices exist whose ID is greater
-                            // than the maximum possible value, so just return
-                            // an empty list.
-                            Err(_) => return Ok(Box::new(vec![].into_iter())),
-                        }
-                    }
-                    None => Uuid::default(),
-                };
+                let start_id = q.start_id.unwrap_or_default();
 
                 let mut iter: Box<dyn Iterator<Item = Result<VertexItem>>> =
-                    Box::new(vertex_manager.iterate_for_range(next_uuid));
+                    Box::new(vertex_manager.iterate_for_range(start_id));
 
                 if let Some(ref t) = q.t {
                     iter = Box::new(iter.filter(move |item| match item {
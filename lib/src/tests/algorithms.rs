@@ -0,0 +1,157 @@
+use super::super::{Datastore, EdgeQueryExt, SpecificEdgeQuery, Transaction};
+use crate::models;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+// Creates a triangle a - b - c, plus a `likes` edge a -> d and a self-loop
+// on a, with edges pointing in both directions. Returns the ids of a
+// through d.
+fn create_triangle<D: Datastore>(datastore: &mut D) -> [Uuid; 4] {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type").unwrap();
+    let follows_t = models::Type::new("follows").unwrap();
+    let likes_t = models::Type::new("likes").unwrap();
+    let ids = [
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t).unwrap(),
+    ];
+
+    for (outbound_id, t, inbound_id) in &[
+        (ids[0], &follows_t, ids[1]),
+        (ids[1], &follows_t, ids[0]),
+        (ids[2], &follows_t, ids[1]),
+        (ids[0], &follows_t, ids[2]),
+        (ids[0], &follows_t, ids[0]),
+        (ids[0], &likes_t, ids[3]),
+    ] {
+        let key = models::EdgeKey::new(*outbound_id, (*t).clone(), *inbound_id);
+        assert!(trans.create_edge(&key).unwrap());
+    }
+
+    ids
+}
+
+pub fn should_get_a_triangle_count<D: Datastore>(datastore: &mut D) {
+    let ids = create_triangle(datastore);
+    let trans = datastore.transaction().unwrap();
+    let follows = [models::Type::new("follows").unwrap()];
+
+    assert_eq!(trans.get_triangle_count(ids[0], &[]).unwrap(), 1);
+    assert_eq!(trans.get_triangle_count(ids[1], &follows).unwrap(), 1);
+    assert_eq!(trans.get_triangle_count(ids[3], &[]).unwrap(), 0);
+    assert_eq!(trans.get_triangle_count(Uuid::default(), &follows).unwrap(), 0);
+}
+
+pub fn should_get_a_local_clustering_coefficient<D: Datastore>(datastore: &mut D) {
+    let ids = create_triangle(datastore);
+    let trans = datastore.transaction().unwrap();
+    let follows = [models::Type::new("follows").unwrap()];
+
+    // a has three neighbors (b, c and d), of which one pair is connected
+    let coefficient = trans.get_local_clustering_coefficient(ids[0], &[]).unwrap();
+    assert!((coefficient - 1.0 / 3.0).abs() < f64::EPSILON);
+    let coefficient = trans.get_local_clustering_coefficient(ids[0], &follows).unwrap();
+    assert!((coefficient - 1.0).abs() < f64::EPSILON);
+    let coefficient = trans.get_local_clustering_coefficient(ids[3], &[]).unwrap();
+    assert!(coefficient.abs() < f64::EPSILON);
+}
+
+pub fn should_take_a_random_walk<D: Datastore>(datastore: &mut D) {
+    let ids = create_triangle(datastore);
+    let trans = datastore.transaction().unwrap();
+    let likes = [models::Type::new("likes").unwrap()];
+
+    let path = trans.random_walk(ids[0], 10, &[], None).unwrap();
+    assert_eq!(path[0], ids[0]);
+    assert!(path.len() <= 11);
+
+    // d has no outbound edges, so the walk ends there
+    if path.len() < 11 {
+        assert_eq!(path[path.len() - 1], ids[3]);
+    }
+
+    assert_eq!(
+        trans.random_walk(ids[0], 10, &likes, None).unwrap(),
+        vec![ids[0], ids[3]]
+    );
+    assert_eq!(
+        trans
+            .random_walk(ids[0], 10, &[likes[0].clone(), likes[0].clone()], None)
+            .unwrap(),
+        vec![ids[0], ids[3]]
+    );
+    assert_eq!(trans.random_walk(ids[0], 0, &[], None).unwrap(), vec![ids[0]]);
+    assert!(trans.random_walk(Uuid::default(), 10, &[], None).unwrap().is_empty());
+}
+
+pub fn should_take_a_weighted_random_walk<D: Datastore>(datastore: &mut D) {
+    let ids = create_triangle(datastore);
+    let trans = datastore.transaction().unwrap();
+    let follows_t = models::Type::new("follows").unwrap();
+
+    // Only a -> b and b -> a have weights, so the walk alternates between
+    // them
+    for (outbound_id, inbound_id, weight) in &[
+        (ids[0], ids[1], JsonValue::from(2)),
+        (ids[1], ids[0], JsonValue::from(0.5)),
+        (ids[0], ids[2], JsonValue::from(0)),
+        (ids[0], ids[0], JsonValue::from("heavy")),
+    ] {
+        let q = SpecificEdgeQuery::single(models::EdgeKey::new(*outbound_id, follows_t.clone(), *inbound_id));
+        trans.set_edge_properties(q.property("weight"), weight).unwrap();
+    }
+
+    let path = trans.random_walk(ids[0], 4, &[], Some("weight")).unwrap();
+    assert_eq!(path, vec![ids[0], ids[1], ids[0], ids[1], ids[0]]);
+
+    // c -> b has no weight, so the walk can't leave c
+    assert_eq!(trans.random_walk(ids[2], 4, &[], Some("weight")).unwrap(), vec![ids[2]]);
+
+    // Weights near the largest float still work, even though their sum
+    // overflows
+    for inbound_id in &[ids[1], ids[2]] {
+        let q = SpecificEdgeQuery::single(models::EdgeKey::new(ids[0], follows_t.clone(), *inbound_id));
+        trans
+            .set_edge_properties(q.property("weight"), &JsonValue::from(1e308))
+            .unwrap();
+    }
+
+    let path = trans.random_walk(ids[0], 1, &[], Some("weight")).unwrap();
+    assert_eq!(path.len(), 2);
+    assert!(path[1] == ids[1] || path[1] == ids[2]);
+}
+
+pub fn should_get_common_neighbors<D: Datastore>(datastore: &mut D) {
+    let ids = create_triangle(datastore);
+    let trans = datastore.transaction().unwrap();
+    let likes = [models::Type::new("likes").unwrap()];
+
+    assert_eq!(trans.get_common_neighbors(ids[0], ids[1], &[]).unwrap(), vec![ids[2]]);
+    assert_eq!(trans.get_common_neighbors(ids[1], ids[3], &[]).unwrap(), vec![ids[0]]);
+    assert!(trans.get_common_neighbors(ids[0], ids[1], &likes).unwrap().is_empty());
+    assert!(trans
+        .get_common_neighbors(ids[0], Uuid::default(), &[])
+        .unwrap()
+        .is_empty());
+}
+
+pub fn should_get_similarity_scores<D: Datastore>(datastore: &mut D) {
+    let ids = create_triangle(datastore);
+    let trans = datastore.transaction().unwrap();
+
+    // a's neighbors are b, c and d, and b's are a and c
+    let jaccard = trans.get_jaccard_similarity(ids[0], ids[1], &[]).unwrap();
+    assert!((jaccard - 0.25).abs() < f64::EPSILON);
+    let jaccard = trans
+        .get_jaccard_similarity(Uuid::default(), Uuid::default(), &[])
+        .unwrap();
+    assert!(jaccard.abs() < f64::EPSILON);
+
+    // Their only common neighbor, c, has two neighbors
+    let adamic_adar = trans.get_adamic_adar_index(ids[0], ids[1], &[]).unwrap();
+    assert!((adamic_adar - 1.0 / 2.0f64.ln()).abs() < f64::EPSILON);
+    let adamic_adar = trans.get_adamic_adar_index(ids[2], ids[3], &[]).unwrap();
+    assert!((adamic_adar - 1.0 / 3.0f64.ln()).abs() < f64::EPSILON);
+}
@@ -1,6 +1,6 @@
 use super::super::{
-    BulkInsertItem, Datastore, EdgeKey, EdgeQueryExt, SpecificEdgeQuery, SpecificVertexQuery, Transaction, Type,
-    Vertex, VertexQueryExt,
+    BulkInsertItem, Datastore, Difference, EdgeDirection, EdgeKey, EdgeQueryExt, MemoryDatastore, SpecificEdgeQuery,
+    SpecificVertexQuery, Transaction, Type, Vertex, VertexQueryExt,
 };
 use chrono::offset::Utc;
 use chrono::Timelike;
@@ -115,3 +115,175 @@ pub fn should_bulk_insert_an_invalid_edge<D: Datastore>(datastore: &mut D) {
     let items = vec![BulkInsertItem::Edge(EdgeKey::new(v2.id, edge_t, v1.id))];
     assert!(datastore.bulk_insert(items.into_iter()).is_ok());
 }
+
+pub fn should_copy_into_another_datastore<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type").unwrap();
+    let outbound_v = Vertex::new(vertex_t.clone());
+    let inbound_v = Vertex::new(vertex_t);
+    let edge_t = Type::new("test_edge_type").unwrap();
+    let key = EdgeKey::new(outbound_v.id, edge_t, inbound_v.id);
+
+    let items = vec![
+        BulkInsertItem::Vertex(inbound_v.clone()),
+        BulkInsertItem::Vertex(outbound_v.clone()),
+        BulkInsertItem::Edge(key.clone()),
+        BulkInsertItem::VertexProperty(outbound_v.id, "vertex_property".to_string(), JsonValue::Bool(true)),
+        BulkInsertItem::EdgeProperty(key.clone(), "edge_property".to_string(), JsonValue::Bool(false)),
+    ];
+    datastore.bulk_insert(items.into_iter()).unwrap();
+
+    let copy = MemoryDatastore::default();
+    datastore.copy_into(&copy).unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    let copy_trans = copy.transaction().unwrap();
+    assert_eq!(
+        copy_trans.get_vertex_count().unwrap(),
+        trans.get_vertex_count().unwrap()
+    );
+
+    let vertices = copy_trans
+        .get_vertices(SpecificVertexQuery::new(vec![outbound_v.id, inbound_v.id]))
+        .unwrap();
+    assert_eq!(vertices.len(), 2);
+    assert_eq!(
+        copy_trans
+            .get_edge_count(outbound_v.id, None, EdgeDirection::Outbound)
+            .unwrap(),
+        1
+    );
+
+    let vertex_properties = copy_trans
+        .get_vertex_properties(SpecificVertexQuery::single(outbound_v.id).property("vertex_property"))
+        .unwrap();
+    assert_eq!(vertex_properties.len(), 1);
+    assert_eq!(vertex_properties[0].value, JsonValue::Bool(true));
+
+    let edge_properties = copy_trans
+        .get_edge_properties(SpecificEdgeQuery::single(key).property("edge_property"))
+        .unwrap();
+    assert_eq!(edge_properties.len(), 1);
+    assert_eq!(edge_properties[0].value, JsonValue::Bool(false));
+}
+
+pub fn should_check_and_repair_integrity<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type").unwrap();
+    let v1 = Vertex::new(vertex_t.clone());
+    let v2 = Vertex::new(vertex_t.clone());
+    let missing_id = Vertex::new(vertex_t.clone()).id;
+    let edge_t = Type::new("test_edge_type").unwrap();
+    let valid_key = EdgeKey::new(v1.id, edge_t.clone(), v2.id);
+    let outbound_dangling_key = EdgeKey::new(v1.id, edge_t.clone(), missing_id);
+    let inbound_dangling_key = EdgeKey::new(missing_id, edge_t, v2.id);
+
+    let items = vec![
+        BulkInsertItem::Vertex(v1.clone()),
+        BulkInsertItem::Vertex(v2.clone()),
+        BulkInsertItem::Edge(valid_key.clone()),
+    ];
+    datastore.bulk_insert(items.into_iter()).unwrap();
+    let items = vec![
+        BulkInsertItem::Edge(outbound_dangling_key.clone()),
+        BulkInsertItem::Edge(inbound_dangling_key.clone()),
+    ];
+    datastore.bulk_insert(items.into_iter()).unwrap();
+
+    // Datastores may or may not have rejected the dangling edges
+    let dangling = datastore.check_integrity().unwrap();
+    assert!(dangling.len() <= 2);
+    assert!(dangling
+        .iter()
+        .all(|key| key == &outbound_dangling_key || key == &inbound_dangling_key));
+
+    assert_eq!(datastore.repair_integrity(&vertex_t).unwrap(), dangling);
+    assert!(datastore.check_integrity().unwrap().is_empty());
+
+    let trans = datastore.transaction().unwrap();
+    assert_eq!(trans.get_vertex_count().unwrap(), 2);
+    let edges = trans
+        .get_edges(SpecificVertexQuery::new(vec![v1.id, v2.id]).outbound())
+        .unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].key, valid_key);
+}
+
+pub fn should_diff_against_another_datastore<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type").unwrap();
+    let v1 = Vertex::new(vertex_t.clone());
+    let v2 = Vertex::new(vertex_t.clone());
+    let v3 = Vertex::new(vertex_t.clone());
+    let v4 = Vertex::new(vertex_t);
+    let edge_t = Type::new("test_edge_type").unwrap();
+    let e1 = EdgeKey::new(v1.id, edge_t.clone(), v2.id);
+    let e2 = EdgeKey::new(v1.id, edge_t.clone(), v3.id);
+    let e3 = EdgeKey::new(v3.id, edge_t, v1.id);
+
+    let items = vec![
+        BulkInsertItem::Vertex(v1.clone()),
+        BulkInsertItem::Vertex(v2.clone()),
+        BulkInsertItem::Vertex(v4.clone()),
+        BulkInsertItem::Edge(e1.clone()),
+        BulkInsertItem::VertexProperty(v1.id, "name".to_string(), JsonValue::from("x")),
+        BulkInsertItem::EdgeProperty(e1.clone(), "weight".to_string(), JsonValue::from(1)),
+    ];
+    datastore.bulk_insert(items.into_iter()).unwrap();
+
+    let copy = MemoryDatastore::default();
+    datastore.copy_into(&copy).unwrap();
+    assert_eq!(datastore.diff(&copy).unwrap(), vec![]);
+
+    let copy_trans = copy.transaction().unwrap();
+    copy_trans
+        .set_vertex_properties(
+            SpecificVertexQuery::single(v1.id).property("name"),
+            &JsonValue::from("y"),
+        )
+        .unwrap();
+    copy_trans.delete_vertices(SpecificVertexQuery::single(v4.id)).unwrap();
+    copy_trans.create_vertex(&v3).unwrap();
+    copy_trans
+        .set_vertex_properties(
+            SpecificVertexQuery::single(v3.id).property("new"),
+            &JsonValue::Bool(true),
+        )
+        .unwrap();
+    copy_trans
+        .set_edge_properties(
+            SpecificEdgeQuery::single(e1.clone()).property("weight"),
+            &JsonValue::from(2),
+        )
+        .unwrap();
+    copy_trans.create_edge(&e2).unwrap();
+    copy_trans.create_edge(&e3).unwrap();
+
+    let differences = datastore.diff(&copy).unwrap();
+    let expected = vec![
+        Difference::VertexPropertyChanged {
+            id: v1.id,
+            name: "name".to_string(),
+            old: Some(JsonValue::from("x")),
+            new: Some(JsonValue::from("y")),
+        },
+        Difference::VertexRemoved(v4),
+        Difference::VertexAdded(v3.clone()),
+        Difference::VertexPropertyChanged {
+            id: v3.id,
+            name: "new".to_string(),
+            old: None,
+            new: Some(JsonValue::Bool(true)),
+        },
+        Difference::EdgePropertyChanged {
+            key: e1,
+            name: "weight".to_string(),
+            old: Some(JsonValue::from(1)),
+            new: Some(JsonValue::from(2)),
+        },
+        Difference::EdgeAdded(e2),
+        Difference::EdgeAdded(e3),
+    ];
+    assert_eq!(differences.len(), expected.len());
+
+    for difference in &expected {
+        assert!(differences.contains(difference), "missing {:?}", difference);
+    }
+}
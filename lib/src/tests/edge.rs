@@ -184,6 +184,53 @@ pub fn should_get_an_inbound_edge_count<D: Datastore>(datastore: &mut D) {
     assert_eq!(count, 1);
 }
 
+pub fn should_get_edge_types<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let other_t = models::Type::new("test_other_edge_type").unwrap();
+    trans
+        .create_edge(&EdgeKey::new(outbound_id, other_t.clone(), outbound_id))
+        .unwrap();
+
+    let types = trans.get_edge_types().unwrap();
+    assert_eq!(types.len(), 2);
+    assert_eq!(types[&models::Type::new("test_edge_type").unwrap()], 5);
+    assert_eq!(types[&other_t], 1);
+}
+
+pub fn should_get_adjacent_edges<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type").unwrap();
+    let other_t = models::Type::new("test_other_edge_type").unwrap();
+    let inbound_key = EdgeKey::new(inbound_ids[0], other_t.clone(), outbound_id);
+    trans.create_edge(&inbound_key).unwrap();
+
+    let adjacent = trans.get_adjacent(outbound_id, None, 10).unwrap();
+    assert_eq!(adjacent.len(), 6);
+
+    for (direction, edge) in &adjacent[..5] {
+        assert_eq!(*direction, EdgeDirection::Outbound);
+        assert_eq!(edge.key.outbound_id, outbound_id);
+        assert_eq!(edge.key.t, t);
+    }
+
+    assert_eq!(adjacent[5].0, EdgeDirection::Inbound);
+    assert_eq!(adjacent[5].1.key, inbound_key);
+
+    let adjacent = trans.get_adjacent(outbound_id, Some(&other_t), 10).unwrap();
+    assert_eq!(adjacent.len(), 1);
+    assert_eq!(adjacent[0].0, EdgeDirection::Inbound);
+
+    assert_eq!(trans.get_adjacent(outbound_id, None, 3).unwrap().len(), 3);
+    assert!(trans.get_adjacent(outbound_id, None, 0).unwrap().is_empty());
+
+    let adjacent = trans.get_adjacent(inbound_ids[0], None, 10).unwrap();
+    assert_eq!(adjacent.len(), 2);
+    assert_eq!(adjacent[0].0, EdgeDirection::Outbound);
+    assert_eq!(adjacent[1].0, EdgeDirection::Inbound);
+}
+
 pub fn should_get_an_edge_range<D: Datastore>(datastore: &mut D) {
     let (outbound_id, start_time, end_time, _) = create_time_range_queryable_edges(datastore);
     let trans = datastore.transaction().unwrap();
@@ -307,6 +354,41 @@ pub fn should_get_edges<D: Datastore>(datastore: &mut D) {
     check_edge_range(&range, outbound_id, 5);
 }
 
+pub fn should_get_edges_by_keys<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, _, _, inbound_ids) = create_time_range_queryable_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type").unwrap();
+    let keys = vec![
+        EdgeKey::new(outbound_id, t.clone(), inbound_ids[3]),
+        EdgeKey::new(inbound_ids[3], t.clone(), outbound_id),
+        EdgeKey::new(outbound_id, t, inbound_ids[1]),
+    ];
+    let edges = trans.get_edges_by_keys(&keys).unwrap();
+
+    assert_eq!(edges.len(), 3);
+    assert_eq!(edges[0].as_ref().unwrap().key, keys[0]);
+    assert!(edges[1].is_none());
+    assert_eq!(edges[2].as_ref().unwrap().key, keys[2]);
+}
+
+pub fn should_sample_edges<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type").unwrap();
+    let other_t = models::Type::new("test_other_edge_type").unwrap();
+    trans
+        .create_edge(&EdgeKey::new(outbound_id, other_t, outbound_id))
+        .unwrap();
+
+    let sample = trans.sample_edges(Some(&t), 2).unwrap();
+    assert_eq!(sample.len(), 2);
+    assert_ne!(sample[0].key, sample[1].key);
+    assert!(sample.iter().all(|e| e.key.t == t && e.key.outbound_id == outbound_id));
+
+    assert_eq!(trans.sample_edges(Some(&t), 10).unwrap().len(), 5);
+    assert_eq!(trans.sample_edges(None, 10).unwrap().len(), 6);
+}
+
 pub fn should_get_edges_piped<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_vertex_type").unwrap();
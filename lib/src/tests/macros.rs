@@ -18,21 +18,35 @@ macro_rules! full_test_impl {
         define_test!(should_bulk_insert, $code);
         define_test!(should_bulk_insert_a_redundant_vertex, $code);
         define_test!(should_bulk_insert_an_invalid_edge, $code);
+        define_test!(should_copy_into_another_datastore, $code);
+        define_test!(should_check_and_repair_integrity, $code);
+        define_test!(should_diff_against_another_datastore, $code);
 
         // Vertices
         define_test!(should_create_vertex_from_type, $code);
+        define_test!(should_upsert_a_vertex_by_key, $code);
         define_test!(should_get_range_vertices, $code);
+        define_test!(should_get_range_vertices_in_order, $code);
         define_test!(should_get_no_vertices_with_zero_limit, $code);
         define_test!(should_get_range_vertices_out_of_range, $code);
         define_test!(should_get_no_vertices_with_type_filter, $code);
         define_test!(should_get_single_vertex, $code);
         define_test!(should_get_single_vertex_nonexisting, $code);
         define_test!(should_get_vertices, $code);
+        define_test!(should_get_vertices_by_ids, $code);
+        define_test!(should_sample_vertices, $code);
         define_test!(should_get_vertices_piped, $code);
         define_test!(should_get_a_vertex_count, $code);
+        define_test!(should_get_a_vertex_count_by_type, $code);
+        define_test!(should_get_vertex_types, $code);
+        define_test!(should_not_create_a_duplicate_vertex, $code);
         define_test!(should_delete_a_valid_outbound_vertex, $code);
+        define_test!(should_delete_properties_with_a_vertex, $code);
         define_test!(should_delete_a_valid_inbound_vertex, $code);
         define_test!(should_not_delete_an_invalid_vertex, $code);
+        define_test!(should_merge_vertices, $code);
+        define_test!(should_merge_vertices_keeping_duplicate_properties, $code);
+        define_test!(should_not_merge_into_an_invalid_vertex, $code);
 
         // Edges
         define_test!(should_get_a_valid_edge, $code);
@@ -45,6 +59,8 @@ macro_rules! full_test_impl {
         define_test!(should_get_an_edge_count_with_no_type, $code);
         define_test!(should_get_an_edge_count_for_an_invalid_edge, $code);
         define_test!(should_get_an_inbound_edge_count, $code);
+        define_test!(should_get_edge_types, $code);
+        define_test!(should_get_adjacent_edges, $code);
         define_test!(should_get_an_edge_range, $code);
         define_test!(should_get_edges_with_no_type, $code);
         define_test!(should_get_no_edges_for_an_invalid_range, $code);
@@ -53,6 +69,8 @@ macro_rules! full_test_impl {
         define_test!(should_get_edges_with_no_time, $code);
         define_test!(should_get_no_edges_for_reversed_time, $code);
         define_test!(should_get_edges, $code);
+        define_test!(should_get_edges_by_keys, $code);
+        define_test!(should_sample_edges, $code);
         define_test!(should_get_edges_piped, $code);
 
         // Properties
@@ -60,9 +78,30 @@ macro_rules! full_test_impl {
         define_test!(should_not_set_invalid_vertex_properties, $code);
         define_test!(should_not_delete_invalid_vertex_properties, $code);
         define_test!(should_get_all_vertex_properties, $code);
+        define_test!(should_patch_vertex_properties, $code);
+        define_test!(should_find_vertices_with_property, $code);
+        define_test!(should_manage_vertex_labels, $code);
         define_test!(should_handle_edge_properties, $code);
         define_test!(should_not_set_invalid_edge_properties, $code);
         define_test!(should_not_delete_invalid_edge_properties, $code);
         define_test!(should_get_all_edge_properties, $code);
+        define_test!(should_patch_edge_properties, $code);
+
+        // Subgraphs
+        define_test!(should_extract_a_subgraph, $code);
+        define_test!(should_extract_a_subgraph_by_edge_type, $code);
+        define_test!(should_extract_an_empty_subgraph, $code);
+
+        // Algorithms
+        define_test!(should_get_a_triangle_count, $code);
+        define_test!(should_get_a_local_clustering_coefficient, $code);
+        define_test!(should_take_a_random_walk, $code);
+        define_test!(should_take_a_weighted_random_walk, $code);
+        define_test!(should_get_common_neighbors, $code);
+        define_test!(should_get_similarity_scores, $code);
+
+        // Read-only transactions
+        define_test!(should_read_through_a_read_only_transaction, $code);
+        define_test!(should_not_mutate_through_a_read_only_transaction, $code);
     };
 }
@@ -4,17 +4,23 @@
 //! `indradb` crate can reuse them. Generally you can use the convenience macro
 //! `full_test_impl`.
 
+mod algorithms;
 mod bulk_insert;
 mod edge;
 #[macro_use]
 mod macros;
 mod properties;
+mod read_only;
+mod subgraph;
 mod util;
 mod vertex;
 
+pub use self::algorithms::*;
 pub use self::bulk_insert::*;
 pub use self::edge::*;
 pub use self::macros::*;
 pub use self::properties::*;
+pub use self::read_only::*;
+pub use self::subgraph::*;
 pub use self::util::*;
 pub use self::vertex::*;
@@ -1,5 +1,6 @@
 use super::super::{
-    Datastore, EdgeKey, EdgeQueryExt, SpecificEdgeQuery, SpecificVertexQuery, Transaction, Type, Vertex, VertexQueryExt,
+    Datastore, EdgeKey, EdgeQueryExt, PropertyPredicate, RangeVertexQuery, SpecificEdgeQuery, SpecificVertexQuery,
+    Transaction, Type, Vertex, VertexQueryExt, LABELS_PROPERTY,
 };
 use crate::util::generate_random_secret;
 use serde_json::Value as JsonValue;
@@ -37,6 +38,108 @@ pub fn should_handle_vertex_properties<D: Datastore>(datastore: &mut D) {
     assert_eq!(result.len(), 0);
 }
 
+pub fn should_patch_vertex_properties<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type").unwrap();
+    let v = Vertex::new(t);
+    trans.create_vertex(&v).unwrap();
+    let name = format!("vertex-properties-{}", generate_random_secret(8));
+    let q = SpecificVertexQuery::single(v.id).property(name.clone());
+
+    // Patching a missing value applies the patch to null
+    trans
+        .patch_vertex_properties(q.clone(), &json(r#"{"a": 1, "b": null}"#))
+        .unwrap();
+    let result = trans.get_vertex_properties(q.clone()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].value, json(r#"{"a": 1}"#));
+
+    // Patching merges into the existing value
+    trans
+        .patch_vertex_properties(q.clone(), &json(r#"{"b": {"c": 2}}"#))
+        .unwrap();
+    let result = trans.get_vertex_properties(q.clone()).unwrap();
+    assert_eq!(result[0].value, json(r#"{"a": 1, "b": {"c": 2}}"#));
+
+    // Nulls in the patch remove members
+    trans
+        .patch_vertex_properties(q.clone(), &json(r#"{"a": null}"#))
+        .unwrap();
+    let result = trans.get_vertex_properties(q).unwrap();
+    assert_eq!(result[0].value, json(r#"{"b": {"c": 2}}"#));
+
+    // Patching a nonexistent vertex does nothing
+    let q = SpecificVertexQuery::single(Uuid::default()).property(name);
+    trans.patch_vertex_properties(q.clone(), &json(r#"{"a": 1}"#)).unwrap();
+    assert_eq!(trans.get_vertex_properties(q).unwrap().len(), 0);
+}
+
+pub fn should_find_vertices_with_property<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new(format!("scored_vertex_{}", generate_random_secret(8))).unwrap();
+    let name = format!("score-{}", generate_random_secret(8));
+    let mut expected_ids = Vec::new();
+
+    for score in &[0.5, 0.95, 1.0] {
+        let v = Vertex::new(t.clone());
+        trans.create_vertex(&v).unwrap();
+        let q = SpecificVertexQuery::single(v.id).property(name.clone());
+        trans.set_vertex_properties(q, &JsonValue::from(*score)).unwrap();
+
+        if *score > 0.9 {
+            expected_ids.push(v.id);
+        }
+    }
+
+    // Vertices without the property never match
+    trans.create_vertex_from_type(t.clone()).unwrap();
+
+    let q = RangeVertexQuery::new().t(t).property(name);
+    let predicate = PropertyPredicate::Gt(JsonValue::from(0.9));
+    let mut ids: Vec<Uuid> = trans
+        .find_vertices_with_property(q.clone(), &predicate)
+        .unwrap()
+        .into_iter()
+        .map(|v| v.id)
+        .collect();
+    ids.sort();
+    expected_ids.sort();
+    assert_eq!(ids, expected_ids);
+
+    let predicate = PropertyPredicate::Gt(JsonValue::from(1.0));
+    assert_eq!(trans.find_vertices_with_property(q, &predicate).unwrap().len(), 0);
+}
+
+pub fn should_manage_vertex_labels<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type").unwrap();
+    let person = trans.create_vertex_from_type(t.clone()).unwrap();
+    let company = trans.create_vertex_from_type(t).unwrap();
+
+    assert!(trans.add_vertex_label(person, "person").unwrap());
+    assert!(trans.add_vertex_label(person, "employee").unwrap());
+    assert!(!trans.add_vertex_label(person, "person").unwrap());
+    assert!(trans.add_vertex_label(company, "employer").unwrap());
+    assert!(!trans.add_vertex_label(Uuid::default(), "person").unwrap());
+
+    let labels = trans
+        .get_vertex_properties(SpecificVertexQuery::single(person).property(LABELS_PROPERTY))
+        .unwrap();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].value, JsonValue::from(vec!["employee", "person"]));
+
+    let q = SpecificVertexQuery::new(vec![person, company]);
+    let vertices = trans.get_vertices_with_label(q.clone(), "employee").unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, person);
+
+    assert!(trans.remove_vertex_label(person, "employee").unwrap());
+    assert!(!trans.remove_vertex_label(person, "employee").unwrap());
+    assert!(!trans.remove_vertex_label(company, "person").unwrap());
+    assert!(trans.get_vertices_with_label(q.clone(), "employee").unwrap().is_empty());
+    assert_eq!(trans.get_vertices_with_label(q, "person").unwrap().len(), 1);
+}
+
 pub fn should_get_all_vertex_properties<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let t = Type::new("a_vertex").unwrap();
@@ -138,6 +241,33 @@ pub fn should_handle_edge_properties<D: Datastore>(datastore: &mut D) {
     assert_eq!(result.len(), 0);
 }
 
+pub fn should_patch_edge_properties<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = Type::new("test_vertex_type").unwrap();
+    let outbound_v = Vertex::new(vertex_t.clone());
+    let inbound_v = Vertex::new(vertex_t);
+    trans.create_vertex(&outbound_v).unwrap();
+    trans.create_vertex(&inbound_v).unwrap();
+    let edge_t = Type::new("test_edge_type").unwrap();
+    let key = EdgeKey::new(outbound_v.id, edge_t.clone(), inbound_v.id);
+    let name = format!("edge-properties-{}", generate_random_secret(8));
+    let q = SpecificEdgeQuery::single(key.clone()).property(name.clone());
+    trans.create_edge(&key).unwrap();
+
+    trans.set_edge_properties(q.clone(), &json(r#"{"a": 1}"#)).unwrap();
+    trans.patch_edge_properties(q.clone(), &json(r#"{"b": [2]}"#)).unwrap();
+    let result = trans.get_edge_properties(q).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, key);
+    assert_eq!(result[0].value, json(r#"{"a": 1, "b": [2]}"#));
+
+    // Patching a nonexistent edge does nothing
+    let key = EdgeKey::new(outbound_v.id, edge_t, Uuid::default());
+    let q = SpecificEdgeQuery::single(key).property(name);
+    trans.patch_edge_properties(q.clone(), &json(r#"{"a": 1}"#)).unwrap();
+    assert_eq!(trans.get_edge_properties(q).unwrap().len(), 0);
+}
+
 pub fn should_get_all_edge_properties<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = Type::new("test_vertex_type").unwrap();
@@ -206,3 +336,7 @@ pub fn should_not_delete_invalid_edge_properties<D: Datastore>(datastore: &mut D
         .delete_edge_properties(SpecificEdgeQuery::single(key).property("bleh"))
         .unwrap();
 }
+
+fn json(s: &str) -> JsonValue {
+    serde_json::from_str(s).unwrap()
+}
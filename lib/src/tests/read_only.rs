@@ -0,0 +1,77 @@
+use super::super::{
+    Datastore, EdgeKey, EdgeQueryExt, Error, SpecificEdgeQuery, SpecificVertexQuery, Transaction, Type, Vertex,
+    VertexQueryExt,
+};
+use super::util::create_edges;
+use serde_json::Value as JsonValue;
+
+pub fn should_read_through_a_read_only_transaction<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.read_transaction().unwrap();
+
+    let vertices = trans.get_vertices(SpecificVertexQuery::single(outbound_id)).unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, outbound_id);
+
+    let edges = trans
+        .get_edges(SpecificVertexQuery::single(outbound_id).outbound())
+        .unwrap();
+    assert_eq!(edges.len(), inbound_ids.len());
+}
+
+pub fn should_not_mutate_through_a_read_only_transaction<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.read_transaction().unwrap();
+    let vertex_t = Type::new("test_vertex_type").unwrap();
+    let edge_t = Type::new("test_edge_type").unwrap();
+    let key = EdgeKey::new(outbound_id, edge_t, inbound_ids[0]);
+    let value = JsonValue::Bool(true);
+
+    assert!(matches!(
+        trans.create_vertex(&Vertex::new(vertex_t.clone())),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(trans.create_vertex_from_type(vertex_t), Err(Error::ReadOnly)));
+    assert!(matches!(
+        trans.delete_vertices(SpecificVertexQuery::single(outbound_id)),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(trans.create_edge(&key), Err(Error::ReadOnly)));
+    assert!(matches!(
+        trans.delete_edges(SpecificEdgeQuery::single(key.clone())),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(
+        trans.set_vertex_properties(SpecificVertexQuery::single(outbound_id).property("foo"), &value),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(
+        trans.delete_vertex_properties(SpecificVertexQuery::single(outbound_id).property("foo")),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(
+        trans.set_edge_properties(SpecificEdgeQuery::single(key.clone()).property("foo"), &value),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(
+        trans.delete_edge_properties(SpecificEdgeQuery::single(key).property("foo")),
+        Err(Error::ReadOnly)
+    ));
+
+    // Nothing should have changed underneath
+    let trans = datastore.transaction().unwrap();
+    assert_eq!(
+        trans
+            .get_vertices(SpecificVertexQuery::single(outbound_id))
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        trans
+            .get_edges(SpecificVertexQuery::single(outbound_id).outbound())
+            .unwrap()
+            .len(),
+        inbound_ids.len()
+    );
+}
@@ -0,0 +1,102 @@
+use super::super::{Datastore, Transaction};
+use crate::models;
+use uuid::Uuid;
+
+// Creates the graph a -> b -> c -> d, with `follows` edges, plus `likes`
+// edges b -> e and c -> e. Returns the ids of a through e.
+fn create_path<D: Datastore>(datastore: &mut D) -> [Uuid; 5] {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type").unwrap();
+    let follows_t = models::Type::new("follows").unwrap();
+    let likes_t = models::Type::new("likes").unwrap();
+    let ids = [
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t.clone()).unwrap(),
+        trans.create_vertex_from_type(vertex_t).unwrap(),
+    ];
+
+    for (outbound_id, t, inbound_id) in &[
+        (ids[0], &follows_t, ids[1]),
+        (ids[1], &follows_t, ids[2]),
+        (ids[2], &follows_t, ids[3]),
+        (ids[1], &likes_t, ids[4]),
+        (ids[2], &likes_t, ids[4]),
+    ] {
+        let key = models::EdgeKey::new(*outbound_id, (*t).clone(), *inbound_id);
+        assert!(trans.create_edge(&key).unwrap());
+    }
+
+    ids
+}
+
+fn vertex_ids(subgraph: &models::Subgraph) -> Vec<Uuid> {
+    subgraph.vertices.iter().map(|v| v.id).collect()
+}
+
+fn edge_ids(subgraph: &models::Subgraph) -> Vec<(Uuid, Uuid)> {
+    subgraph
+        .edges
+        .iter()
+        .map(|e| (e.key.outbound_id, e.key.inbound_id))
+        .collect()
+}
+
+fn sorted<T: Ord>(mut items: Vec<T>) -> Vec<T> {
+    items.sort();
+    items
+}
+
+pub fn should_extract_a_subgraph<D: Datastore>(datastore: &mut D) {
+    let [a, b, c, d, e] = create_path(datastore);
+    let trans = datastore.transaction().unwrap();
+
+    let subgraph = trans.extract_subgraph(vec![a], 0, &[]).unwrap();
+    assert_eq!(vertex_ids(&subgraph), vec![a]);
+    assert!(subgraph.edges.is_empty());
+
+    let subgraph = trans.extract_subgraph(vec![a], 1, &[]).unwrap();
+    assert_eq!(vertex_ids(&subgraph), sorted(vec![a, b]));
+    assert_eq!(edge_ids(&subgraph), vec![(a, b)]);
+
+    // Edges between vertices at the maximum depth are included
+    let subgraph = trans.extract_subgraph(vec![a], 2, &[]).unwrap();
+    assert_eq!(vertex_ids(&subgraph), sorted(vec![a, b, c, e]));
+    assert_eq!(
+        sorted(edge_ids(&subgraph)),
+        sorted(vec![(a, b), (b, c), (b, e), (c, e)])
+    );
+
+    // Edges are followed in both directions
+    let subgraph = trans.extract_subgraph(vec![d], 1, &[]).unwrap();
+    assert_eq!(vertex_ids(&subgraph), sorted(vec![c, d]));
+    assert_eq!(edge_ids(&subgraph), vec![(c, d)]);
+}
+
+pub fn should_extract_a_subgraph_by_edge_type<D: Datastore>(datastore: &mut D) {
+    let [a, b, c, d, e] = create_path(datastore);
+    let trans = datastore.transaction().unwrap();
+    let follows_t = models::Type::new("follows").unwrap();
+    let likes_t = models::Type::new("likes").unwrap();
+    let both_ts = [likes_t.clone(), follows_t.clone()];
+
+    let subgraph = trans.extract_subgraph(vec![a], 3, &[follows_t]).unwrap();
+    assert_eq!(vertex_ids(&subgraph), sorted(vec![a, b, c, d]));
+    assert_eq!(sorted(edge_ids(&subgraph)), sorted(vec![(a, b), (b, c), (c, d)]));
+
+    let subgraph = trans.extract_subgraph(vec![e], 1, &[likes_t]).unwrap();
+    assert_eq!(vertex_ids(&subgraph), sorted(vec![b, c, e]));
+    assert_eq!(sorted(edge_ids(&subgraph)), sorted(vec![(b, e), (c, e)]));
+
+    let subgraph = trans.extract_subgraph(vec![e], 1, &both_ts).unwrap();
+    assert_eq!(vertex_ids(&subgraph), sorted(vec![b, c, e]));
+    assert_eq!(sorted(edge_ids(&subgraph)), sorted(vec![(b, c), (b, e), (c, e)]));
+}
+
+pub fn should_extract_an_empty_subgraph<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let subgraph = trans.extract_subgraph(vec![Uuid::default()], 3, &[]).unwrap();
+    assert!(subgraph.vertices.is_empty());
+    assert!(subgraph.edges.is_empty());
+}
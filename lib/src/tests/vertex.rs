@@ -1,4 +1,7 @@
-use super::super::{Datastore, EdgeQueryExt, RangeVertexQuery, SpecificVertexQuery, Transaction, VertexQueryExt};
+use super::super::{
+    Datastore, EdgeQueryExt, MergePolicy, RangeVertexQuery, SpecificEdgeQuery, SpecificVertexQuery, Transaction,
+    VertexQueryExt,
+};
 use super::util::{create_edge_from, create_edges};
 use crate::models;
 use serde_json::Value as JsonValue;
@@ -11,6 +14,19 @@ pub fn should_create_vertex_from_type<D: Datastore>(datastore: &mut D) {
     trans.create_vertex_from_type(t).unwrap();
 }
 
+pub fn should_upsert_a_vertex_by_key<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type").unwrap();
+    let id = trans.upsert_vertex_by_key("test_key", t.clone()).unwrap();
+    assert_eq!(trans.upsert_vertex_by_key("test_key", t.clone()).unwrap(), id);
+    assert_ne!(trans.upsert_vertex_by_key("other_test_key", t.clone()).unwrap(), id);
+
+    let vertices = trans.get_vertices(SpecificVertexQuery::single(id)).unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].t, t);
+    assert_eq!(trans.get_vertex_count().unwrap(), 2);
+}
+
 pub fn should_get_range_vertices<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let mut inserted_ids = create_vertices(&trans);
@@ -32,6 +48,25 @@ pub fn should_get_range_vertices<D: Datastore>(datastore: &mut D) {
     }
 }
 
+pub fn should_get_range_vertices_in_order<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let inserted_ids = create_vertices(&trans);
+
+    // The start ID is inclusive, and results are ordered by ID
+    let range = trans
+        .get_vertices(RangeVertexQuery::new().start_id(inserted_ids[2]))
+        .unwrap();
+    let range_ids: Vec<Uuid> = range.iter().map(|v| v.id).collect();
+    let mut sorted_range_ids = range_ids.clone();
+    sorted_range_ids.sort();
+    assert_eq!(range_ids, sorted_range_ids);
+    assert!(!range_ids.contains(&inserted_ids[0]));
+    assert!(!range_ids.contains(&inserted_ids[1]));
+    assert!(range_ids.contains(&inserted_ids[2]));
+    assert!(range_ids.contains(&inserted_ids[3]));
+    assert!(range_ids.contains(&inserted_ids[4]));
+}
+
 pub fn should_get_no_vertices_with_zero_limit<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     create_vertices(&trans);
@@ -106,6 +141,37 @@ pub fn should_get_vertices<D: Datastore>(datastore: &mut D) {
     }
 }
 
+pub fn should_get_vertices_by_ids<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let inserted_ids = create_vertices(&trans);
+    let ids = vec![inserted_ids[2], Uuid::default(), inserted_ids[0], inserted_ids[2]];
+    let vertices = trans.get_vertices_by_ids(&ids).unwrap();
+
+    assert_eq!(vertices.len(), 4);
+    assert_eq!(vertices[0].as_ref().unwrap().id, inserted_ids[2]);
+    assert!(vertices[1].is_none());
+    assert_eq!(vertices[2].as_ref().unwrap().id, inserted_ids[0]);
+    assert_eq!(vertices[3].as_ref().unwrap().id, inserted_ids[2]);
+}
+
+pub fn should_sample_vertices<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let inserted_ids = create_vertices(&trans);
+    let t = models::Type::new("test_vertex_type").unwrap();
+    trans
+        .create_vertex_from_type(models::Type::new("test_other_vertex_type").unwrap())
+        .unwrap();
+
+    let sample = trans.sample_vertices(Some(&t), 3).unwrap();
+    assert_eq!(sample.len(), 3);
+    assert_eq!(sample.iter().map(|v| v.id).collect::<HashSet<Uuid>>().len(), 3);
+    assert!(sample.iter().all(|v| v.t == t && inserted_ids.contains(&v.id)));
+
+    assert_eq!(trans.sample_vertices(Some(&t), 10).unwrap().len(), 5);
+    assert_eq!(trans.sample_vertices(None, 10).unwrap().len(), 6);
+    assert!(trans.sample_vertices(None, 0).unwrap().is_empty());
+}
+
 pub fn should_get_vertices_piped<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_vertex_type").unwrap();
@@ -156,6 +222,31 @@ pub fn should_get_vertices_piped<D: Datastore>(datastore: &mut D) {
     assert_eq!(range[0], v);
 }
 
+pub fn should_get_vertex_types<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    create_vertices(&trans);
+    let other_t = models::Type::new("test_other_vertex_type").unwrap();
+    trans.create_vertex_from_type(other_t.clone()).unwrap();
+
+    let types = trans.get_vertex_types().unwrap();
+    assert_eq!(types.len(), 2);
+    assert_eq!(types[&models::Type::new("test_vertex_type").unwrap()], 5);
+    assert_eq!(types[&other_t], 1);
+}
+
+pub fn should_not_create_a_duplicate_vertex<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex = models::Vertex::new(models::Type::new("test_vertex_type").unwrap());
+    assert!(trans.create_vertex(&vertex).unwrap());
+
+    // The original type should be kept
+    let duplicate = models::Vertex::with_id(vertex.id, models::Type::new("foo").unwrap());
+    assert!(!trans.create_vertex(&duplicate).unwrap());
+    let range = trans.get_vertices(SpecificVertexQuery::single(vertex.id)).unwrap();
+    assert_eq!(range.len(), 1);
+    assert_eq!(range[0].t, vertex.t);
+}
+
 pub fn should_delete_a_valid_outbound_vertex<D: Datastore>(datastore: &mut D) {
     let (outbound_id, _) = create_edges(datastore);
     let trans = datastore.transaction().unwrap();
@@ -173,6 +264,26 @@ pub fn should_delete_a_valid_outbound_vertex<D: Datastore>(datastore: &mut D) {
     assert_eq!(count, 0);
 }
 
+pub fn should_delete_properties_with_a_vertex<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let q = SpecificVertexQuery::single(outbound_id);
+    trans
+        .set_vertex_properties(q.clone().property("foo"), &JsonValue::Bool(true))
+        .unwrap();
+    trans
+        .set_edge_properties(q.clone().outbound().property("foo"), &JsonValue::Bool(true))
+        .unwrap();
+    trans.delete_vertices(q.clone()).unwrap();
+
+    let vertex_properties = trans.get_vertex_properties(q.property("foo")).unwrap();
+    assert_eq!(vertex_properties.len(), 0);
+    let edge_properties = trans
+        .get_edge_properties(SpecificVertexQuery::new(inbound_ids.to_vec()).inbound().property("foo"))
+        .unwrap();
+    assert_eq!(edge_properties.len(), 0);
+}
+
 pub fn should_delete_a_valid_inbound_vertex<D: Datastore>(datastore: &mut D) {
     let (_, inbound_ids) = create_edges(datastore);
     let inbound_id = inbound_ids[0];
@@ -195,6 +306,135 @@ pub fn should_not_delete_an_invalid_vertex<D: Datastore>(datastore: &mut D) {
         .unwrap();
 }
 
+pub fn should_merge_vertices<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type").unwrap();
+    let edge_t = models::Type::new("test_edge_type").unwrap();
+    let survivor = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let duplicate_1 = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let duplicate_2 = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let other = trans.create_vertex_from_type(vertex_t).unwrap();
+
+    let keys = [
+        models::EdgeKey::new(duplicate_1, edge_t.clone(), other),
+        models::EdgeKey::new(other, edge_t.clone(), duplicate_2),
+        models::EdgeKey::new(duplicate_1, edge_t.clone(), duplicate_2),
+        models::EdgeKey::new(survivor, edge_t.clone(), other),
+    ];
+
+    for key in &keys {
+        trans.create_edge(key).unwrap();
+    }
+
+    let set_vertex_property = |id, name, value: &str| {
+        let q = SpecificVertexQuery::single(id).property(name);
+        trans.set_vertex_properties(q, &JsonValue::from(value)).unwrap();
+    };
+    set_vertex_property(survivor, "name", "survivor");
+    set_vertex_property(duplicate_1, "name", "duplicate 1");
+    set_vertex_property(duplicate_1, "email", "duplicate 1");
+    set_vertex_property(duplicate_2, "email", "duplicate 2");
+    trans
+        .set_edge_properties(
+            SpecificEdgeQuery::single(keys[0].clone()).property("weight"),
+            &JsonValue::from(2),
+        )
+        .unwrap();
+    trans
+        .set_edge_properties(
+            SpecificEdgeQuery::single(keys[3].clone()).property("weight"),
+            &JsonValue::from(1),
+        )
+        .unwrap();
+
+    let missing = Uuid::default();
+    assert!(trans
+        .merge_vertices(
+            survivor,
+            vec![duplicate_1, missing, survivor, duplicate_2],
+            MergePolicy::KeepSurvivor
+        )
+        .unwrap());
+
+    let vertices = trans
+        .get_vertices(SpecificVertexQuery::new(vec![survivor, duplicate_1, duplicate_2]))
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, survivor);
+
+    let props = trans
+        .get_all_vertex_properties(SpecificVertexQuery::single(survivor))
+        .unwrap();
+    let props: Vec<(String, JsonValue)> = props[0]
+        .props
+        .iter()
+        .map(|p| (p.name.clone(), p.value.clone()))
+        .collect();
+    assert_eq!(props.len(), 2);
+    assert!(props.contains(&("name".to_string(), JsonValue::from("survivor"))));
+    assert!(props.contains(&("email".to_string(), JsonValue::from("duplicate 1"))));
+
+    let edges: HashSet<models::EdgeKey> = trans
+        .get_edges(SpecificVertexQuery::new(vec![survivor, other]).outbound())
+        .unwrap()
+        .into_iter()
+        .map(|e| e.key)
+        .collect();
+    let expected: HashSet<models::EdgeKey> = vec![
+        models::EdgeKey::new(survivor, edge_t.clone(), other),
+        models::EdgeKey::new(other, edge_t.clone(), survivor),
+        models::EdgeKey::new(survivor, edge_t.clone(), survivor),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(edges, expected);
+
+    let weights = trans
+        .get_edge_properties(SpecificEdgeQuery::single(keys[3].clone()).property("weight"))
+        .unwrap();
+    assert_eq!(weights.len(), 1);
+    assert_eq!(weights[0].value, JsonValue::from(1));
+}
+
+pub fn should_merge_vertices_keeping_duplicate_properties<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type").unwrap();
+    let survivor = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let duplicate_1 = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let duplicate_2 = trans.create_vertex_from_type(vertex_t).unwrap();
+
+    for (id, value) in &[(survivor, 0), (duplicate_1, 1), (duplicate_2, 2)] {
+        let q = SpecificVertexQuery::single(*id).property("name");
+        trans.set_vertex_properties(q, &JsonValue::from(*value)).unwrap();
+    }
+
+    assert!(trans
+        .merge_vertices(survivor, vec![duplicate_2, duplicate_1], MergePolicy::KeepDuplicate)
+        .unwrap());
+    let props = trans
+        .get_vertex_properties(SpecificVertexQuery::single(survivor).property("name"))
+        .unwrap();
+    assert_eq!(props.len(), 1);
+    assert_eq!(props[0].value, JsonValue::from(1));
+}
+
+pub fn should_not_merge_into_an_invalid_vertex<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type").unwrap();
+    let duplicate = trans.create_vertex_from_type(vertex_t).unwrap();
+
+    assert!(!trans
+        .merge_vertices(Uuid::default(), vec![duplicate], MergePolicy::KeepSurvivor)
+        .unwrap());
+    assert_eq!(
+        trans
+            .get_vertices(SpecificVertexQuery::single(duplicate))
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
 pub fn should_get_a_vertex_count<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_vertex_type").unwrap();
@@ -204,6 +444,23 @@ pub fn should_get_a_vertex_count<D: Datastore>(datastore: &mut D) {
     assert!(count >= 1);
 }
 
+pub fn should_get_a_vertex_count_by_type<D: Datastore>(datastore: &mut D) {
+    let counted_t = models::Type::new("test_counted_vertex_type").unwrap();
+    let other_t = models::Type::new("test_uncounted_vertex_type").unwrap();
+
+    // Enough vertices to span more than one page of the default
+    // implementation
+    let items = (0..1001)
+        .map(|_| models::BulkInsertItem::Vertex(models::Vertex::new(counted_t.clone())))
+        .chain(Some(models::BulkInsertItem::Vertex(models::Vertex::new(other_t))));
+    datastore.bulk_insert(items).unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    assert_eq!(trans.get_vertex_count_by_type(&counted_t).unwrap(), 1001);
+    let missing_t = models::Type::new("test_missing_vertex_type").unwrap();
+    assert_eq!(trans.get_vertex_count_by_type(&missing_t).unwrap(), 0);
+}
+
 fn create_vertices<T>(trans: &T) -> Vec<Uuid>
 where
     T: Transaction,
@@ -0,0 +1,336 @@
+use crate::errors::Result;
+use crate::models;
+use crate::traits::{Datastore, Transaction};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use tracing::Span;
+use uuid::Uuid;
+
+fn traced<R, F>(span: Span, f: F) -> Result<R>
+where
+    F: FnOnce() -> Result<R>,
+{
+    let _entered = span.enter();
+    let result = f();
+
+    if let Err(ref err) = result {
+        tracing::warn!(error = %err, "datastore operation failed");
+    }
+
+    result
+}
+
+/// A datastore that opens a `tracing` span for every call made through it,
+/// and through its transactions. Spans are named after the method, carry
+/// the query, IDs and types involved, and are emitted at the debug level.
+/// Methods with default implementations are forwarded under a span of
+/// their own. Errors are reported as warning events inside the span.
+#[derive(Debug)]
+pub struct TracedDatastore<D: Datastore> {
+    inner: D,
+}
+
+impl<D: Datastore> TracedDatastore<D> {
+    /// Creates a new traced datastore.
+    ///
+    /// # Arguments
+    /// * `inner`: The datastore to wrap.
+    pub fn new(inner: D) -> Self {
+        TracedDatastore { inner }
+    }
+}
+
+impl<D: Datastore> Datastore for TracedDatastore<D> {
+    type Trans = TracedTransaction<D::Trans>;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        let inner = traced(tracing::debug_span!("transaction"), || self.inner.transaction())?;
+        Ok(TracedTransaction { inner })
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        traced(tracing::debug_span!("bulk_insert"), || self.inner.bulk_insert(items))
+    }
+
+    fn copy_into<O: Datastore>(&self, other: &O) -> Result<()> {
+        traced(tracing::debug_span!("copy_into"), || self.inner.copy_into(other))
+    }
+
+    fn check_integrity(&self) -> Result<Vec<models::EdgeKey>> {
+        traced(tracing::debug_span!("check_integrity"), || self.inner.check_integrity())
+    }
+
+    fn repair_integrity(&self, t: &models::Type) -> Result<Vec<models::EdgeKey>> {
+        let span = tracing::debug_span!("repair_integrity", t = %t.0);
+        traced(span, || self.inner.repair_integrity(t))
+    }
+
+    fn diff<O: Datastore>(&self, other: &O) -> Result<Vec<models::Difference>> {
+        traced(tracing::debug_span!("diff"), || self.inner.diff(other))
+    }
+}
+
+/// A transaction that opens a `tracing` span for every call made through it.
+#[derive(Debug)]
+pub struct TracedTransaction<T: Transaction> {
+    inner: T,
+}
+
+impl<T: Transaction> Transaction for TracedTransaction<T> {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        let span = tracing::debug_span!("create_vertex", id = %vertex.id, t = %vertex.t.0);
+        traced(span, || self.inner.create_vertex(vertex))
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        let span = tracing::debug_span!("create_vertex_from_type", t = %t.0);
+        traced(span, || self.inner.create_vertex_from_type(t))
+    }
+
+    fn upsert_vertex_by_key(&self, key: &str, t: models::Type) -> Result<Uuid> {
+        let span = tracing::debug_span!("upsert_vertex_by_key", key = %key, t = %t.0);
+        traced(span, || self.inner.upsert_vertex_by_key(key, t))
+    }
+
+    fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>> {
+        let q = q.into();
+        let span = tracing::debug_span!("get_vertices", query = ?q);
+        traced(span, || self.inner.get_vertices(q))
+    }
+
+    fn get_vertices_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Option<models::Vertex>>> {
+        let span = tracing::debug_span!("get_vertices_by_ids", ids = ?ids);
+        traced(span, || self.inner.get_vertices_by_ids(ids))
+    }
+
+    fn sample_vertices(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Vertex>> {
+        let span = tracing::debug_span!("sample_vertices", t = ?t, n = %n);
+        traced(span, || self.inner.sample_vertices(t, n))
+    }
+
+    fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()> {
+        let q = q.into();
+        let span = tracing::debug_span!("delete_vertices", query = ?q);
+        traced(span, || self.inner.delete_vertices(q))
+    }
+
+    fn merge_vertices(&self, survivor: Uuid, duplicates: Vec<Uuid>, policy: models::MergePolicy) -> Result<bool> {
+        let span = tracing::debug_span!(
+            "merge_vertices",
+            survivor = %survivor,
+            duplicates = ?duplicates,
+            policy = ?policy
+        );
+        traced(span, || self.inner.merge_vertices(survivor, duplicates, policy))
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        traced(tracing::debug_span!("get_vertex_count"), || {
+            self.inner.get_vertex_count()
+        })
+    }
+
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        let span = tracing::debug_span!("get_vertex_count_by_type", t = %t.0);
+        traced(span, || self.inner.get_vertex_count_by_type(t))
+    }
+
+    fn get_vertex_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        traced(tracing::debug_span!("get_vertex_types"), || {
+            self.inner.get_vertex_types()
+        })
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let span = tracing::debug_span!(
+            "create_edge",
+            outbound_id = %key.outbound_id,
+            t = %key.t.0,
+            inbound_id = %key.inbound_id
+        );
+        traced(span, || self.inner.create_edge(key))
+    }
+
+    fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>> {
+        let q = q.into();
+        let span = tracing::debug_span!("get_edges", query = ?q);
+        traced(span, || self.inner.get_edges(q))
+    }
+
+    fn get_edges_by_keys(&self, keys: &[models::EdgeKey]) -> Result<Vec<Option<models::Edge>>> {
+        let span = tracing::debug_span!("get_edges_by_keys", keys = ?keys);
+        traced(span, || self.inner.get_edges_by_keys(keys))
+    }
+
+    fn sample_edges(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Edge>> {
+        let span = tracing::debug_span!("sample_edges", t = ?t, n = %n);
+        traced(span, || self.inner.sample_edges(t, n))
+    }
+
+    fn get_edge_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        traced(tracing::debug_span!("get_edge_types"), || self.inner.get_edge_types())
+    }
+
+    fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()> {
+        let q = q.into();
+        let span = tracing::debug_span!("delete_edges", query = ?q);
+        traced(span, || self.inner.delete_edges(q))
+    }
+
+    fn extract_subgraph(&self, roots: Vec<Uuid>, depth: u32, edge_types: &[models::Type]) -> Result<models::Subgraph> {
+        let span = tracing::debug_span!(
+            "extract_subgraph",
+            roots = ?roots,
+            depth = %depth,
+            edge_types = ?edge_types
+        );
+        traced(span, || self.inner.extract_subgraph(roots, depth, edge_types))
+    }
+
+    fn get_triangle_count(&self, id: Uuid, edge_types: &[models::Type]) -> Result<u64> {
+        let span = tracing::debug_span!("get_triangle_count", id = %id, edge_types = ?edge_types);
+        traced(span, || self.inner.get_triangle_count(id, edge_types))
+    }
+
+    fn get_local_clustering_coefficient(&self, id: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        let span = tracing::debug_span!("get_local_clustering_coefficient", id = %id, edge_types = ?edge_types);
+        traced(span, || self.inner.get_local_clustering_coefficient(id, edge_types))
+    }
+
+    fn get_common_neighbors(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<Vec<Uuid>> {
+        let span = tracing::debug_span!("get_common_neighbors", a = %a, b = %b, edge_types = ?edge_types);
+        traced(span, || self.inner.get_common_neighbors(a, b, edge_types))
+    }
+
+    fn get_jaccard_similarity(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        let span = tracing::debug_span!("get_jaccard_similarity", a = %a, b = %b, edge_types = ?edge_types);
+        traced(span, || self.inner.get_jaccard_similarity(a, b, edge_types))
+    }
+
+    fn get_adamic_adar_index(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        let span = tracing::debug_span!("get_adamic_adar_index", a = %a, b = %b, edge_types = ?edge_types);
+        traced(span, || self.inner.get_adamic_adar_index(a, b, edge_types))
+    }
+
+    fn random_walk(
+        &self,
+        start: Uuid,
+        length: u32,
+        edge_types: &[models::Type],
+        weight_property: Option<&str>,
+    ) -> Result<Vec<Uuid>> {
+        let span = tracing::debug_span!(
+            "random_walk",
+            start = %start,
+            length = %length,
+            edge_types = ?edge_types,
+            weight_property = ?weight_property
+        );
+        traced(span, || {
+            self.inner.random_walk(start, length, edge_types, weight_property)
+        })
+    }
+
+    fn get_adjacent(
+        &self,
+        id: Uuid,
+        t: Option<&models::Type>,
+        limit: u32,
+    ) -> Result<Vec<(models::EdgeDirection, models::Edge)>> {
+        let span = tracing::debug_span!("get_adjacent", id = %id, t = ?t, limit = %limit);
+        traced(span, || self.inner.get_adjacent(id, t, limit))
+    }
+
+    fn get_edge_count(&self, id: Uuid, t: Option<&models::Type>, direction: models::EdgeDirection) -> Result<u64> {
+        let span = tracing::debug_span!("get_edge_count", id = %id, t = ?t, direction = ?direction);
+        traced(span, || self.inner.get_edge_count(id, t, direction))
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        let span = tracing::debug_span!("get_vertex_properties", query = ?q);
+        traced(span, || self.inner.get_vertex_properties(q))
+    }
+
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        let span = tracing::debug_span!("find_vertices_with_property", query = ?q, predicate = ?predicate);
+        traced(span, || self.inner.find_vertices_with_property(q, predicate))
+    }
+
+    fn add_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        let span = tracing::debug_span!("add_vertex_label", id = %id, label = %label);
+        traced(span, || self.inner.add_vertex_label(id, label))
+    }
+
+    fn remove_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        let span = tracing::debug_span!("remove_vertex_label", id = %id, label = %label);
+        traced(span, || self.inner.remove_vertex_label(id, label))
+    }
+
+    fn get_vertices_with_label<Q: Into<models::VertexQuery>>(&self, q: Q, label: &str) -> Result<Vec<models::Vertex>> {
+        let q = q.into();
+        let span = tracing::debug_span!("get_vertices_with_label", query = ?q, label = %label);
+        traced(span, || self.inner.get_vertices_with_label(q, label))
+    }
+
+    fn get_all_vertex_properties<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::VertexProperties>> {
+        let q = q.into();
+        let span = tracing::debug_span!("get_all_vertex_properties", query = ?q);
+        traced(span, || self.inner.get_all_vertex_properties(q))
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()> {
+        let span = tracing::debug_span!("set_vertex_properties", query = ?q);
+        traced(span, || self.inner.set_vertex_properties(q, value))
+    }
+
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        let span = tracing::debug_span!("patch_vertex_properties", query = ?q);
+        traced(span, || self.inner.patch_vertex_properties(q, patch))
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<()> {
+        let span = tracing::debug_span!("delete_vertex_properties", query = ?q);
+        traced(span, || self.inner.delete_vertex_properties(q))
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        let span = tracing::debug_span!("get_edge_properties", query = ?q);
+        traced(span, || self.inner.get_edge_properties(q))
+    }
+
+    fn get_all_edge_properties<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::EdgeProperties>> {
+        let q = q.into();
+        let span = tracing::debug_span!("get_all_edge_properties", query = ?q);
+        traced(span, || self.inner.get_all_edge_properties(q))
+    }
+
+    fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()> {
+        let span = tracing::debug_span!("set_edge_properties", query = ?q);
+        traced(span, || self.inner.set_edge_properties(q, value))
+    }
+
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        let span = tracing::debug_span!("patch_edge_properties", query = ?q);
+        traced(span, || self.inner.patch_edge_properties(q, patch))
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()> {
+        let span = tracing::debug_span!("delete_edge_properties", query = ?q);
+        traced(span, || self.inner.delete_edge_properties(q))
+    }
+}
+
+#[cfg(all(test, feature = "test-suite"))]
+mod tests {
+    use super::TracedDatastore;
+    use crate::MemoryDatastore;
+
+    full_test_impl!(TracedDatastore::new(MemoryDatastore::default()));
+}
@@ -1,10 +1,289 @@
 use crate::errors::{Error, Result};
 use crate::models;
 use crate::models::{EdgeQueryExt, VertexQueryExt};
+use crate::read_only::ReadOnlyTransaction;
+use crate::util::{generate_uuid_v5, merge_patch, next_uuid, Reservoir};
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde_json::value::Value as JsonValue;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::vec::Vec;
 use uuid::Uuid;
 
+// The number of vertices fetched at a time by default implementations that
+// page through the datastore.
+const PAGE_SIZE: u32 = 1000;
+
+// Pages through every vertex in order of id, `PAGE_SIZE` at a time. `f` is
+// called with the range query for each page; it runs the query, possibly
+// with a type filter added, handles the results, and returns the ids of the
+// vertices in the page so the next page can start after them.
+fn for_each_vertex_page<F>(mut f: F) -> Result<()>
+where
+    F: FnMut(models::RangeVertexQuery) -> Result<Vec<Uuid>>,
+{
+    let mut q = models::RangeVertexQuery::new().limit(PAGE_SIZE);
+
+    loop {
+        let ids = f(q.clone())?;
+        let next_start_id = ids.iter().max().cloned().map(next_uuid);
+        let is_last_page = ids.len() < PAGE_SIZE as usize;
+
+        match next_start_id {
+            Some(Ok(start_id)) if !is_last_page => q = q.start_id(start_id),
+            _ => return Ok(()),
+        }
+    }
+}
+
+// Gets the edges of the given types, in either direction, on a set of
+// vertices. An empty list of types gets edges of any type. Each edge is
+// returned once, even if both of its vertices are in the set.
+fn get_incident_edges<T: Transaction + ?Sized>(
+    trans: &T,
+    ids: &[Uuid],
+    edge_types: &[models::Type],
+) -> Result<BTreeMap<models::EdgeKey, models::Edge>> {
+    let mut edges = BTreeMap::new();
+
+    if ids.is_empty() {
+        return Ok(edges);
+    }
+
+    let types: Vec<Option<&models::Type>> = if edge_types.is_empty() {
+        vec![None]
+    } else {
+        edge_types.iter().map(Some).collect()
+    };
+
+    for t in types {
+        for direction in &[models::EdgeDirection::Outbound, models::EdgeDirection::Inbound] {
+            let inner = models::SpecificVertexQuery::new(ids.to_vec()).into();
+            let mut q = models::PipeEdgeQuery::new(Box::new(inner), *direction);
+
+            if let Some(t) = t {
+                q = q.t(t.clone());
+            }
+
+            for edge in trans.get_edges(q)? {
+                edges.insert(edge.key.clone(), edge);
+            }
+        }
+    }
+
+    Ok(edges)
+}
+
+// Gets the ids of the existing vertices connected to a vertex by edges of
+// the given types, in either direction, excluding the vertex itself. An
+// empty list of types considers edges of any type.
+fn get_neighbors<T: Transaction + ?Sized>(trans: &T, id: Uuid, edge_types: &[models::Type]) -> Result<HashSet<Uuid>> {
+    let ids: Vec<Uuid> = get_incident_edges(trans, &[id], edge_types)?
+        .into_keys()
+        .map(|key| {
+            if key.outbound_id == id {
+                key.inbound_id
+            } else {
+                key.outbound_id
+            }
+        })
+        .filter(|neighbor_id| *neighbor_id != id)
+        .collect::<HashSet<Uuid>>()
+        .into_iter()
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    Ok(trans
+        .get_vertices(models::SpecificVertexQuery::new(ids))?
+        .into_iter()
+        .map(|v| v.id)
+        .collect())
+}
+
+/// Everything a merge moves onto the survivor, read before anything is
+/// written so that wrappers can check it up front.
+pub(crate) struct MergePlan {
+    /// The duplicates that exist, without the survivor or repeats.
+    pub(crate) duplicates: Vec<Uuid>,
+    /// The duplicates' vertex properties, in merge order.
+    pub(crate) vertex_props: Vec<models::NamedProperty>,
+    /// The duplicates' edges with their endpoints remapped to the survivor,
+    /// along with their properties.
+    pub(crate) edges: Vec<(models::EdgeKey, Vec<models::NamedProperty>)>,
+}
+
+/// Reads what merging the duplicates into the survivor would move. Returns
+/// `None` if the survivor doesn't exist.
+pub(crate) fn plan_merge<T: Transaction + ?Sized>(
+    trans: &T,
+    survivor: Uuid,
+    duplicates: Vec<Uuid>,
+) -> Result<Option<MergePlan>> {
+    if trans
+        .get_vertices(models::SpecificVertexQuery::single(survivor))?
+        .is_empty()
+    {
+        return Ok(None);
+    }
+
+    let existing: HashSet<Uuid> = trans
+        .get_vertices(models::SpecificVertexQuery::new(duplicates.clone()))?
+        .into_iter()
+        .map(|v| v.id)
+        .collect();
+    let mut seen = HashSet::new();
+    let duplicates: Vec<Uuid> = duplicates
+        .into_iter()
+        .filter(|id| *id != survivor && existing.contains(id) && seen.insert(*id))
+        .collect();
+
+    let remap = |id: Uuid| if existing.contains(&id) { survivor } else { id };
+    let mut vertex_props = Vec::new();
+    let mut edges = Vec::new();
+
+    for id in &duplicates {
+        for vertex_properties in trans.get_all_vertex_properties(models::SpecificVertexQuery::single(*id))? {
+            vertex_props.extend(vertex_properties.props);
+        }
+
+        let keys: Vec<models::EdgeKey> = get_incident_edges(trans, &[*id], &[])?.into_keys().collect();
+
+        for edge_properties in trans.get_all_edge_properties(models::SpecificEdgeQuery::new(keys))? {
+            let key = edge_properties.edge.key;
+            let key = models::EdgeKey::new(remap(key.outbound_id), key.t, remap(key.inbound_id));
+            edges.push((key, edge_properties.props));
+        }
+    }
+
+    Ok(Some(MergePlan {
+        duplicates,
+        vertex_props,
+        edges,
+    }))
+}
+
+/// Deletes the duplicates in a merge plan, then writes their edges and
+/// properties to the survivor.
+pub(crate) fn apply_merge<T: Transaction + ?Sized>(
+    trans: &T,
+    survivor: Uuid,
+    plan: MergePlan,
+    policy: models::MergePolicy,
+) -> Result<()> {
+    if plan.duplicates.is_empty() {
+        return Ok(());
+    }
+
+    trans.delete_vertices(models::SpecificVertexQuery::new(plan.duplicates))?;
+    let keep_existing = policy == models::MergePolicy::KeepSurvivor;
+
+    for prop in plan.vertex_props {
+        let q = models::SpecificVertexQuery::single(survivor).property(prop.name);
+
+        if keep_existing && !trans.get_vertex_properties(q.clone())?.is_empty() {
+            continue;
+        }
+
+        trans.set_vertex_properties(q, &prop.value)?;
+    }
+
+    for (key, props) in plan.edges {
+        trans.create_edge(&key)?;
+
+        for prop in props {
+            let q = models::SpecificEdgeQuery::single(key.clone()).property(prop.name);
+
+            if keep_existing && !trans.get_edge_properties(q.clone())?.is_empty() {
+                continue;
+            }
+
+            trans.set_edge_properties(q, &prop.value)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Gets the labels of a vertex. A missing or malformed labels property is
+// treated as no labels.
+fn get_labels<T: Transaction + ?Sized>(trans: &T, q: models::VertexPropertyQuery) -> Result<Vec<String>> {
+    let labels = match trans.get_vertex_properties(q)?.pop() {
+        Some(prop) => match prop.value {
+            JsonValue::Array(values) => values
+                .into_iter()
+                .filter_map(|value| match value {
+                    JsonValue::String(label) => Some(label),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+
+    Ok(labels)
+}
+
+// Gets the outbound edges of a set of vertices, with their properties,
+// keyed by edge.
+fn get_all_outbound_edge_properties<T: Transaction + ?Sized>(
+    trans: &T,
+    ids: Vec<Uuid>,
+) -> Result<BTreeMap<models::EdgeKey, Vec<models::NamedProperty>>> {
+    Ok(trans
+        .get_all_edge_properties(models::SpecificVertexQuery::new(ids).outbound())?
+        .into_iter()
+        .map(|ep| (ep.edge.key, ep.props))
+        .collect())
+}
+
+// Pairs up two lists of properties by name, returning the ones that differ
+// as (name, old value, new value).
+fn diff_properties(
+    old: Vec<models::NamedProperty>,
+    new: Vec<models::NamedProperty>,
+) -> Vec<(String, Option<JsonValue>, Option<JsonValue>)> {
+    let mut new: BTreeMap<String, JsonValue> = new.into_iter().map(|p| (p.name, p.value)).collect();
+    let mut differences = Vec::new();
+
+    for prop in old {
+        let new_value = new.remove(&prop.name);
+
+        if new_value.as_ref() != Some(&prop.value) {
+            differences.push((prop.name, Some(prop.value), new_value));
+        }
+    }
+
+    for (name, value) in new {
+        differences.push((name, None, Some(value)));
+    }
+
+    differences
+}
+
+// Records edges that only exist in the second of two datastores being
+// diffed, along with their properties.
+fn push_added_edges(
+    differences: &mut Vec<models::Difference>,
+    edges: BTreeMap<models::EdgeKey, Vec<models::NamedProperty>>,
+) {
+    for (key, props) in edges {
+        differences.push(models::Difference::EdgeAdded(key.clone()));
+
+        for (name, old, new) in diff_properties(Vec::new(), props) {
+            differences.push(models::Difference::EdgePropertyChanged {
+                key: key.clone(),
+                name,
+                old,
+                new,
+            });
+        }
+    }
+}
+
 /// Specifies a datastore implementation.
 ///
 /// # Errors
@@ -16,6 +295,12 @@ pub trait Datastore {
     /// Creates a new transaction.
     fn transaction(&self) -> Result<Self::Trans>;
 
+    /// Creates a new transaction that only allows reads. Calls that would
+    /// mutate the datastore return `Error::ReadOnly`.
+    fn read_transaction(&self) -> Result<ReadOnlyTransaction<Self::Trans>> {
+        Ok(ReadOnlyTransaction::new(self.transaction()?))
+    }
+
     /// Bulk inserts many vertices, edges, and/or properties.
     ///
     /// Note that datastores have discretion on how to approach safeguard vs
@@ -56,6 +341,258 @@ pub trait Datastore {
 
         Ok(())
     }
+
+    /// Copies every vertex, edge and property in this datastore into another
+    /// one, e.g. to take a disposable copy for testing.
+    ///
+    /// The copy is made a page at a time through the other datastore's
+    /// `bulk_insert`, so it carries the same tradeoffs, and copied edges get
+    /// new update datetimes. Changes made to this datastore during the copy
+    /// may or may not be included.
+    ///
+    /// # Arguments
+    /// * `other`: The datastore to copy into.
+    fn copy_into<O: Datastore>(&self, other: &O) -> Result<()> {
+        let trans = self.transaction()?;
+
+        // Copy all of the vertices before any edges, so that both ends of
+        // every edge exist by the time it's inserted
+        for_each_vertex_page(|q| {
+            let page = trans.get_all_vertex_properties(q)?;
+            let ids = page.iter().map(|vp| vp.vertex.id).collect();
+            let mut items = Vec::new();
+
+            for vertex_properties in page {
+                let id = vertex_properties.vertex.id;
+                items.push(models::BulkInsertItem::Vertex(vertex_properties.vertex));
+
+                for prop in vertex_properties.props {
+                    items.push(models::BulkInsertItem::VertexProperty(id, prop.name, prop.value));
+                }
+            }
+
+            other.bulk_insert(items.into_iter())?;
+            Ok(ids)
+        })?;
+
+        // Then copy edges, a page of outbound vertices at a time
+        for_each_vertex_page(|q| {
+            let ids: Vec<Uuid> = trans.get_vertices(q)?.into_iter().map(|v| v.id).collect();
+            let page = trans.get_all_edge_properties(models::SpecificVertexQuery::new(ids.clone()).outbound())?;
+            let mut items = Vec::new();
+
+            for edge_properties in page {
+                let key = edge_properties.edge.key;
+                items.push(models::BulkInsertItem::Edge(key.clone()));
+
+                for prop in edge_properties.props {
+                    items.push(models::BulkInsertItem::EdgeProperty(key.clone(), prop.name, prop.value));
+                }
+            }
+
+            other.bulk_insert(items.into_iter())?;
+            Ok(ids)
+        })
+    }
+
+    /// Finds dangling edges: edges where one of the two vertices doesn't
+    /// exist. These can be created by `bulk_insert`, which doesn't
+    /// necessarily verify that vertices exist before inserting an edge.
+    ///
+    /// Edges are found from the vertex that does exist, so an edge where
+    /// neither vertex exists can't be found.
+    fn check_integrity(&self) -> Result<Vec<models::EdgeKey>> {
+        let trans = self.transaction()?;
+        let mut dangling = Vec::new();
+
+        for_each_vertex_page(|q| {
+            let ids: Vec<Uuid> = trans.get_vertices(q)?.into_iter().map(|v| v.id).collect();
+            let edges = get_incident_edges(&trans, &ids, &[])?;
+
+            let endpoint_ids: Vec<Uuid> = edges
+                .keys()
+                .flat_map(|key| vec![key.outbound_id, key.inbound_id])
+                .collect::<HashSet<Uuid>>()
+                .into_iter()
+                .collect();
+            let existing_ids: HashSet<Uuid> = trans
+                .get_vertices(models::SpecificVertexQuery::new(endpoint_ids))?
+                .into_iter()
+                .map(|v| v.id)
+                .collect();
+
+            for key in edges.into_keys() {
+                if !existing_ids.contains(&key.outbound_id) || !existing_ids.contains(&key.inbound_id) {
+                    dangling.push(key);
+                }
+            }
+
+            Ok(ids)
+        })?;
+
+        Ok(dangling)
+    }
+
+    /// Removes the dangling edges found by `check_integrity`, returning
+    /// them.
+    ///
+    /// Each missing vertex is briefly recreated and then deleted, which
+    /// removes everything that still references it - its remaining edges
+    /// and any of its properties left behind. Readers may see the
+    /// recreated vertices while the repair is running.
+    ///
+    /// # Arguments
+    /// * `t`: The type to recreate missing vertices with.
+    fn repair_integrity(&self, t: &models::Type) -> Result<Vec<models::EdgeKey>> {
+        let dangling = self.check_integrity()?;
+        let trans = self.transaction()?;
+
+        let endpoint_ids: Vec<Uuid> = dangling
+            .iter()
+            .flat_map(|key| vec![key.outbound_id, key.inbound_id])
+            .collect::<HashSet<Uuid>>()
+            .into_iter()
+            .collect();
+        let existing_ids: HashSet<Uuid> = trans
+            .get_vertices(models::SpecificVertexQuery::new(endpoint_ids.clone()))?
+            .into_iter()
+            .map(|v| v.id)
+            .collect();
+        let missing_ids: Vec<Uuid> = endpoint_ids
+            .into_iter()
+            .filter(|id| !existing_ids.contains(id))
+            .collect();
+
+        for id in &missing_ids {
+            trans.create_vertex(&models::Vertex::with_id(*id, t.clone()))?;
+        }
+
+        trans.delete_vertices(models::SpecificVertexQuery::new(missing_ids))?;
+        Ok(dangling)
+    }
+
+    /// Finds the differences between this datastore and another one, going
+    /// from this datastore to the other: vertices, edges and properties
+    /// that were added, removed or changed. Edge update datetimes are
+    /// ignored, so e.g. a datastore and a copy of it made with `copy_into`
+    /// have no differences.
+    ///
+    /// Both datastores are read a page of vertices at a time. As with
+    /// `copy_into`, changes made to either datastore while this runs may or
+    /// may not be seen, and dangling edges are only found from the vertex
+    /// that exists.
+    ///
+    /// # Arguments
+    /// * `other`: The datastore to compare against.
+    fn diff<O: Datastore>(&self, other: &O) -> Result<Vec<models::Difference>> {
+        let trans = self.transaction()?;
+        let other_trans = other.transaction()?;
+        let mut differences = Vec::new();
+
+        // First, go through this datastore's vertices, comparing them and
+        // their outbound edges against the other datastore's
+        for_each_vertex_page(|q| {
+            let page = trans.get_all_vertex_properties(q)?;
+            let ids: Vec<Uuid> = page.iter().map(|vp| vp.vertex.id).collect();
+            let mut other_page: HashMap<Uuid, models::VertexProperties> = other_trans
+                .get_all_vertex_properties(models::SpecificVertexQuery::new(ids.clone()))?
+                .into_iter()
+                .map(|vp| (vp.vertex.id, vp))
+                .collect();
+
+            for vertex_properties in page {
+                let id = vertex_properties.vertex.id;
+
+                match other_page.remove(&id) {
+                    Some(other_vertex_properties) => {
+                        if vertex_properties.vertex.t != other_vertex_properties.vertex.t {
+                            differences.push(models::Difference::VertexChanged {
+                                old: vertex_properties.vertex,
+                                new: other_vertex_properties.vertex,
+                            });
+                        }
+
+                        for (name, old, new) in diff_properties(vertex_properties.props, other_vertex_properties.props)
+                        {
+                            differences.push(models::Difference::VertexPropertyChanged { id, name, old, new });
+                        }
+                    }
+                    None => {
+                        differences.push(models::Difference::VertexRemoved(vertex_properties.vertex));
+
+                        for (name, old, new) in diff_properties(vertex_properties.props, Vec::new()) {
+                            differences.push(models::Difference::VertexPropertyChanged { id, name, old, new });
+                        }
+                    }
+                }
+            }
+
+            let edges = get_all_outbound_edge_properties(&trans, ids.clone())?;
+            let mut other_edges = get_all_outbound_edge_properties(&other_trans, ids.clone())?;
+
+            for (key, props) in edges {
+                let other_props = match other_edges.remove(&key) {
+                    Some(other_props) => other_props,
+                    None => {
+                        differences.push(models::Difference::EdgeRemoved(key.clone()));
+                        Vec::new()
+                    }
+                };
+
+                for (name, old, new) in diff_properties(props, other_props) {
+                    differences.push(models::Difference::EdgePropertyChanged {
+                        key: key.clone(),
+                        name,
+                        old,
+                        new,
+                    });
+                }
+            }
+
+            push_added_edges(&mut differences, other_edges);
+            Ok(ids)
+        })?;
+
+        // Then go through the other datastore's vertices, picking up the
+        // ones that don't exist in this datastore, along with their
+        // outbound edges
+        for_each_vertex_page(|q| {
+            let page = other_trans.get_all_vertex_properties(q)?;
+            let ids: Vec<Uuid> = page.iter().map(|vp| vp.vertex.id).collect();
+            let existing_ids: HashSet<Uuid> = trans
+                .get_vertices(models::SpecificVertexQuery::new(ids.clone()))?
+                .into_iter()
+                .map(|v| v.id)
+                .collect();
+            let mut added_ids = Vec::new();
+
+            for vertex_properties in page {
+                let id = vertex_properties.vertex.id;
+
+                if existing_ids.contains(&id) {
+                    continue;
+                }
+
+                added_ids.push(id);
+                differences.push(models::Difference::VertexAdded(vertex_properties.vertex));
+
+                for (name, old, new) in diff_properties(Vec::new(), vertex_properties.props) {
+                    differences.push(models::Difference::VertexPropertyChanged { id, name, old, new });
+                }
+            }
+
+            if !added_ids.is_empty() {
+                push_added_edges(
+                    &mut differences,
+                    get_all_outbound_edge_properties(&other_trans, added_ids)?,
+                );
+            }
+
+            Ok(ids)
+        })?;
+
+        Ok(differences)
+    }
 }
 
 /// Specifies a transaction implementation, which are returned by datastores.
@@ -91,21 +628,162 @@ pub trait Transaction {
         }
     }
 
+    /// Gets the UUID of the vertex associated with an external key,
+    /// creating the vertex if it doesn't exist. Returns the vertex's UUID.
+    ///
+    /// The UUID is generated from the key, so finding or creating the
+    /// vertex is a single `create_vertex` call, and concurrent upserts of
+    /// the same key can't create duplicates. If the vertex already exists,
+    /// it's left as-is, even if it has a different type.
+    ///
+    /// # Arguments
+    /// * `key`: The external key.
+    /// * `t`: The type of the vertex to create.
+    fn upsert_vertex_by_key(&self, key: &str, t: models::Type) -> Result<Uuid> {
+        let v = models::Vertex::with_id(generate_uuid_v5(key), t);
+        self.create_vertex(&v)?;
+        Ok(v.id)
+    }
+
     /// Gets a range of vertices specified by a query.
     ///
     /// # Arguments
     /// * `q` - The query to run.
     fn get_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<Vec<models::Vertex>>;
 
+    /// Gets many vertices by their UUIDs in a single query. Unlike
+    /// `get_vertices`, the results are in the same order as the UUIDs,
+    /// with `None` for vertices that don't exist.
+    ///
+    /// # Arguments
+    /// * `ids`: The UUIDs of the vertices to get.
+    fn get_vertices_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Option<models::Vertex>>> {
+        let vertices: HashMap<Uuid, models::Vertex> = self
+            .get_vertices(models::SpecificVertexQuery::new(ids.to_vec()))?
+            .into_iter()
+            .map(|v| (v.id, v))
+            .collect();
+        Ok(ids.iter().map(|id| vertices.get(id).cloned()).collect())
+    }
+
+    /// Gets a uniformly random sample of vertices, optionally of a type.
+    /// Returns all of the matching vertices, in no particular order, if
+    /// there are no more than `n` of them.
+    ///
+    /// This reads every matching vertex, a page at a time, but only holds
+    /// on to `n` of them.
+    ///
+    /// # Arguments
+    /// * `t`: The type of vertices to sample, or `None` for any type.
+    /// * `n`: The number of vertices to sample.
+    fn sample_vertices(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Vertex>> {
+        let mut rng = rand::thread_rng();
+        let mut reservoir = Reservoir::new(n as usize);
+
+        for_each_vertex_page(|q| {
+            let q = match t {
+                Some(t) => q.t(t.clone()),
+                None => q,
+            };
+            let page = self.get_vertices(q)?;
+            let ids = page.iter().map(|v| v.id).collect();
+
+            for vertex in page {
+                reservoir.add(vertex, &mut rng);
+            }
+
+            Ok(ids)
+        })?;
+
+        Ok(reservoir.into_items())
+    }
+
     /// Deletes existing vertices specified by a query.
     ///
     /// # Arguments
     /// * `q` - The query to run.
     fn delete_vertices<Q: Into<models::VertexQuery>>(&self, q: Q) -> Result<()>;
 
+    /// Merges duplicate vertices into a surviving vertex. Edges to or from
+    /// the duplicates are moved onto the survivor, properties are copied
+    /// over, and the duplicates are deleted. Returns whether the survivor
+    /// exists - if it doesn't, nothing is changed.
+    ///
+    /// Duplicates are merged in the order given. Duplicates that don't
+    /// exist, or that are the survivor itself, are ignored. An edge between
+    /// the survivor and a duplicate becomes an edge from the survivor to
+    /// itself.
+    ///
+    /// The default implementation reads the duplicates' edges and
+    /// properties, deletes the duplicates, and then writes everything to
+    /// the survivor, so that values that must be unique can move from a
+    /// duplicate to the survivor. It's only atomic if the transaction is;
+    /// otherwise, a failure partway through can lose edges and properties.
+    /// `SchemaTransaction` checks the edges and unique values before
+    /// deleting anything.
+    ///
+    /// # Arguments
+    /// * `survivor`: The id of the vertex to keep.
+    /// * `duplicates`: The ids of the vertices to merge into it.
+    /// * `policy`: Which value to keep when vertex or edge properties
+    ///   conflict.
+    fn merge_vertices(&self, survivor: Uuid, duplicates: Vec<Uuid>, policy: models::MergePolicy) -> Result<bool> {
+        match plan_merge(self, survivor, duplicates)? {
+            Some(plan) => {
+                apply_merge(self, survivor, plan, policy)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     /// Gets the number of vertices in the datastore.
     fn get_vertex_count(&self) -> Result<u64>;
 
+    /// Gets the number of vertices of a given type in the datastore.
+    ///
+    /// The default implementation pages through range queries filtered by
+    /// the type. Datastores that can count more cheaply should override it.
+    ///
+    /// # Arguments
+    /// * `t` - The type of vertices to count.
+    fn get_vertex_count_by_type(&self, t: &models::Type) -> Result<u64> {
+        let mut count = 0;
+
+        for_each_vertex_page(|q| {
+            let ids: Vec<Uuid> = self.get_vertices(q.t(t.clone()))?.into_iter().map(|v| v.id).collect();
+            count += ids.len() as u64;
+            Ok(ids)
+        })?;
+
+        Ok(count)
+    }
+
+    /// Gets the distinct vertex types in the datastore, with the number of
+    /// vertices of each type.
+    ///
+    /// Nothing is kept up to date as vertices are written, so the default
+    /// implementation pages through every vertex on each call, which takes
+    /// time proportional to the size of the datastore. Avoid calling it on
+    /// a hot path. No first or last seen times are returned, since vertices
+    /// aren't timestamped.
+    fn get_vertex_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        let mut types = BTreeMap::new();
+
+        for_each_vertex_page(|q| {
+            let vertices = self.get_vertices(q)?;
+            let ids = vertices.iter().map(|v| v.id).collect();
+
+            for vertex in vertices {
+                *types.entry(vertex.t).or_insert(0) += 1;
+            }
+
+            Ok(ids)
+        })?;
+
+        Ok(types)
+    }
+
     /// Creates a new edge. If the edge already exists, this will update it
     /// with a new update datetime. Returns whether the edge was successfully
     /// created - if this is false, it's because one of the specified vertices
@@ -121,12 +799,406 @@ pub trait Transaction {
     /// * `q` - The query to run.
     fn get_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<Vec<models::Edge>>;
 
+    /// Gets many edges by their keys in a single query. Unlike `get_edges`,
+    /// the results are in the same order as the keys, with `None` for edges
+    /// that don't exist.
+    ///
+    /// # Arguments
+    /// * `keys`: The keys of the edges to get.
+    fn get_edges_by_keys(&self, keys: &[models::EdgeKey]) -> Result<Vec<Option<models::Edge>>> {
+        let edges: HashMap<models::EdgeKey, models::Edge> = self
+            .get_edges(models::SpecificEdgeQuery::new(keys.to_vec()))?
+            .into_iter()
+            .map(|e| (e.key.clone(), e))
+            .collect();
+        Ok(keys.iter().map(|key| edges.get(key).cloned()).collect())
+    }
+
+    /// Gets a uniformly random sample of edges, optionally of a type.
+    /// Returns all of the matching edges, in no particular order, if there
+    /// are no more than `n` of them.
+    ///
+    /// This reads every matching edge, a page of outbound vertices at a
+    /// time, but only holds on to `n` of them.
+    ///
+    /// # Arguments
+    /// * `t`: The type of edges to sample, or `None` for any type.
+    /// * `n`: The number of edges to sample.
+    fn sample_edges(&self, t: Option<&models::Type>, n: u32) -> Result<Vec<models::Edge>> {
+        let mut rng = rand::thread_rng();
+        let mut reservoir = Reservoir::new(n as usize);
+
+        for_each_vertex_page(|q| {
+            let ids: Vec<Uuid> = self.get_vertices(q)?.into_iter().map(|v| v.id).collect();
+            let mut edge_q = models::SpecificVertexQuery::new(ids.clone()).outbound();
+
+            if let Some(t) = t {
+                edge_q = edge_q.t(t.clone());
+            }
+
+            for edge in self.get_edges(edge_q)? {
+                reservoir.add(edge, &mut rng);
+            }
+
+            Ok(ids)
+        })?;
+
+        Ok(reservoir.into_items())
+    }
+
+    /// Gets the distinct edge types in the datastore, with the number of
+    /// edges of each type.
+    ///
+    /// As with `get_vertex_types`, nothing is kept up to date as edges are
+    /// written: the default implementation pages through every vertex and
+    /// its outbound edges on each call, taking time proportional to the
+    /// size of the datastore. No first or last seen times are returned; an
+    /// edge's datetime is reset whenever it's created again, so it can't
+    /// tell when a type was first seen.
+    fn get_edge_types(&self) -> Result<BTreeMap<models::Type, u64>> {
+        let mut types = BTreeMap::new();
+
+        for_each_vertex_page(|q| {
+            let ids: Vec<Uuid> = self.get_vertices(q)?.into_iter().map(|v| v.id).collect();
+
+            for edge in self.get_edges(models::SpecificVertexQuery::new(ids.clone()).outbound())? {
+                *types.entry(edge.key.t).or_insert(0) += 1;
+            }
+
+            Ok(ids)
+        })?;
+
+        Ok(types)
+    }
+
     /// Deletes a set of edges specified by a query.
     ///
     /// # Arguments
     /// * `q` - The query to run.
     fn delete_edges<Q: Into<models::EdgeQuery>>(&self, q: Q) -> Result<()>;
 
+    /// Gets the part of the graph within a number of hops of some root
+    /// vertices: every vertex reachable from the roots in at most `depth`
+    /// steps along edges of the given types, in either direction, and all
+    /// of the edges of those types between them.
+    ///
+    /// # Arguments
+    /// * `roots` - The ids of the vertices to start from. Ids that don't
+    ///   exist are ignored.
+    /// * `depth` - The maximum number of hops from the roots.
+    /// * `edge_types` - The types of edges to follow. If empty, edges of any
+    ///   type are followed.
+    fn extract_subgraph(&self, roots: Vec<Uuid>, depth: u32, edge_types: &[models::Type]) -> Result<models::Subgraph> {
+        let mut vertices: BTreeMap<Uuid, models::Vertex> = self
+            .get_vertices(models::SpecificVertexQuery::new(roots))?
+            .into_iter()
+            .map(|v| (v.id, v))
+            .collect();
+        let mut edges = BTreeMap::new();
+        let mut frontier: Vec<Uuid> = vertices.keys().cloned().collect();
+        let mut level = 0;
+
+        // The edges of the last level are still fetched, so that edges
+        // between vertices at the maximum depth are included
+        while !frontier.is_empty() {
+            let mut next_ids = Vec::new();
+
+            for (key, edge) in get_incident_edges(self, &frontier, edge_types)? {
+                if level < depth {
+                    for id in &[key.outbound_id, key.inbound_id] {
+                        if !vertices.contains_key(id) {
+                            next_ids.push(*id);
+                        }
+                    }
+                }
+
+                edges.insert(key, edge);
+            }
+
+            next_ids.sort();
+            next_ids.dedup();
+            frontier.clear();
+
+            if !next_ids.is_empty() {
+                for vertex in self.get_vertices(models::SpecificVertexQuery::new(next_ids))? {
+                    frontier.push(vertex.id);
+                    vertices.insert(vertex.id, vertex);
+                }
+            }
+
+            level += 1;
+        }
+
+        let edges = edges
+            .into_iter()
+            .filter(|(key, _)| vertices.contains_key(&key.outbound_id) && vertices.contains_key(&key.inbound_id))
+            .map(|(_, edge)| edge)
+            .collect();
+
+        Ok(models::Subgraph::new(vertices.into_values().collect(), edges))
+    }
+
+    /// Gets the number of triangles a vertex is part of, treating the graph
+    /// as undirected: the number of pairs of its neighbors that are
+    /// themselves connected. Edge direction, parallel edges and self-loops
+    /// are ignored.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `edge_types` - The types of edges to consider. If empty, edges of
+    ///   any type are considered.
+    fn get_triangle_count(&self, id: Uuid, edge_types: &[models::Type]) -> Result<u64> {
+        let neighbors = get_neighbors(self, id, edge_types)?;
+        let neighbor_ids: Vec<Uuid> = neighbors.iter().cloned().collect();
+        let mut pairs = HashSet::new();
+
+        for key in get_incident_edges(self, &neighbor_ids, edge_types)?.into_keys() {
+            if key.outbound_id != key.inbound_id
+                && neighbors.contains(&key.outbound_id)
+                && neighbors.contains(&key.inbound_id)
+            {
+                pairs.insert((key.outbound_id.min(key.inbound_id), key.outbound_id.max(key.inbound_id)));
+            }
+        }
+
+        Ok(pairs.len() as u64)
+    }
+
+    /// Gets the local clustering coefficient of a vertex, treating the
+    /// graph as undirected: the fraction of pairs of its neighbors that are
+    /// themselves connected. Vertices with fewer than two neighbors have a
+    /// coefficient of zero.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `edge_types` - The types of edges to consider. If empty, edges of
+    ///   any type are considered.
+    fn get_local_clustering_coefficient(&self, id: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        let degree = get_neighbors(self, id, edge_types)?.len() as u64;
+
+        if degree < 2 {
+            return Ok(0.0);
+        }
+
+        let triangles = self.get_triangle_count(id, edge_types)?;
+        Ok((2 * triangles) as f64 / (degree * (degree - 1)) as f64)
+    }
+
+    /// Gets the ids of the vertices that are neighbors of both of two
+    /// vertices, treating the graph as undirected, in order.
+    ///
+    /// # Arguments
+    /// * `a` - The id of the first vertex.
+    /// * `b` - The id of the second vertex.
+    /// * `edge_types` - The types of edges to consider. If empty, edges of
+    ///   any type are considered.
+    fn get_common_neighbors(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<Vec<Uuid>> {
+        let a_neighbors = get_neighbors(self, a, edge_types)?;
+        let b_neighbors = get_neighbors(self, b, edge_types)?;
+        let mut common: Vec<Uuid> = a_neighbors.intersection(&b_neighbors).cloned().collect();
+        common.sort();
+        Ok(common)
+    }
+
+    /// Gets the Jaccard similarity of two vertices, treating the graph as
+    /// undirected: the number of their common neighbors, divided by the
+    /// number of vertices that are a neighbor of either. Vertices with no
+    /// neighbors have a similarity of zero.
+    ///
+    /// # Arguments
+    /// * `a` - The id of the first vertex.
+    /// * `b` - The id of the second vertex.
+    /// * `edge_types` - The types of edges to consider. If empty, edges of
+    ///   any type are considered.
+    fn get_jaccard_similarity(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        let a_neighbors = get_neighbors(self, a, edge_types)?;
+        let b_neighbors = get_neighbors(self, b, edge_types)?;
+        let union = a_neighbors.union(&b_neighbors).count();
+
+        if union == 0 {
+            return Ok(0.0);
+        }
+
+        Ok(a_neighbors.intersection(&b_neighbors).count() as f64 / union as f64)
+    }
+
+    /// Gets the Adamic-Adar index of two vertices, treating the graph as
+    /// undirected: the sum, over their common neighbors, of one over the
+    /// logarithm of the common neighbor's number of neighbors. This weights
+    /// rarely shared neighbors above commonly shared ones.
+    ///
+    /// # Arguments
+    /// * `a` - The id of the first vertex.
+    /// * `b` - The id of the second vertex.
+    /// * `edge_types` - The types of edges to consider. If empty, edges of
+    ///   any type are considered.
+    fn get_adamic_adar_index(&self, a: Uuid, b: Uuid, edge_types: &[models::Type]) -> Result<f64> {
+        let mut index = 0.0;
+
+        for id in self.get_common_neighbors(a, b, edge_types)? {
+            let degree = get_neighbors(self, id, edge_types)?.len();
+
+            // A common neighbor of two different vertices has at least two
+            // neighbors, so this only skips the case where `a` and `b` are
+            // the same vertex
+            if degree > 1 {
+                index += 1.0 / (degree as f64).ln();
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Takes a random walk along outbound edges, returning the ids of the
+    /// vertices visited, starting with `start`. The walk ends early if it
+    /// reaches a vertex with no edges to follow, and is empty if `start`
+    /// doesn't exist.
+    ///
+    /// # Arguments
+    /// * `start` - The id of the vertex to start from.
+    /// * `length` - The maximum number of steps to take.
+    /// * `edge_types` - The types of edges to follow. If empty, edges of any
+    ///   type are followed. Repeating a type has no effect.
+    /// * `weight_property` - If set, each step picks an edge with
+    ///   probability proportional to the value of this edge property.
+    ///   Edges where it's missing, not a number, or negative have a weight
+    ///   of zero, and are never followed. Otherwise, each step picks an edge
+    ///   uniformly at random.
+    fn random_walk(
+        &self,
+        start: Uuid,
+        length: u32,
+        edge_types: &[models::Type],
+        weight_property: Option<&str>,
+    ) -> Result<Vec<Uuid>> {
+        let mut rng = rand::thread_rng();
+
+        if self
+            .get_vertices(models::SpecificVertexQuery::single(start))?
+            .is_empty()
+        {
+            return Ok(Vec::new());
+        }
+
+        // Deduplicate the types, so that a repeated type doesn't add its
+        // edges twice and skew the sampling
+        let types: Vec<Option<&models::Type>> = if edge_types.is_empty() {
+            vec![None]
+        } else {
+            edge_types
+                .iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(Some)
+                .collect()
+        };
+        let mut path = vec![start];
+        let mut current = start;
+
+        for _ in 0..length {
+            let mut keys = Vec::new();
+
+            for t in &types {
+                let mut q = models::SpecificVertexQuery::single(current).outbound();
+
+                if let Some(t) = t {
+                    q = q.t((*t).clone());
+                }
+
+                keys.extend(self.get_edges(q)?.into_iter().map(|e| e.key));
+            }
+
+            let next = match weight_property {
+                Some(name) if !keys.is_empty() => {
+                    let weights: HashMap<models::EdgeKey, f64> = self
+                        .get_edge_properties(models::SpecificEdgeQuery::new(keys.clone()).property(name))?
+                        .into_iter()
+                        .filter_map(|p| match p.value.as_f64() {
+                            Some(weight) if weight > 0.0 && weight.is_finite() => Some((p.key, weight)),
+                            _ => None,
+                        })
+                        .collect();
+
+                    // Scale the weights by the largest one, so that large
+                    // weights can't sum to infinity
+                    let max = weights.values().cloned().fold(0.0, f64::max);
+                    let weights: HashMap<models::EdgeKey, f64> =
+                        weights.into_iter().map(|(key, weight)| (key, weight / max)).collect();
+                    let total: f64 = weights.values().sum();
+
+                    if total > 0.0 {
+                        let mut remaining = rng.gen_range(0.0..total);
+                        let mut next = None;
+
+                        for key in &keys {
+                            if let Some(weight) = weights.get(key) {
+                                next = Some(key.inbound_id);
+
+                                if remaining < *weight {
+                                    break;
+                                }
+
+                                remaining -= weight;
+                            }
+                        }
+
+                        next
+                    } else {
+                        None
+                    }
+                }
+                _ => keys.choose(&mut rng).map(|key| key.inbound_id),
+            };
+
+            match next {
+                Some(next) => {
+                    path.push(next);
+                    current = next;
+                }
+                None => break,
+            }
+        }
+
+        Ok(path)
+    }
+
+    /// Gets the edges on a vertex in both directions, each paired with its
+    /// direction relative to the vertex. Outbound edges come first, and the
+    /// limit applies to the combined results. Self-loops are both outbound
+    /// and inbound, so they're included once for each.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `t` - Only get edges of a specified type.
+    /// * `limit` - The maximum number of edges to get.
+    fn get_adjacent(
+        &self,
+        id: Uuid,
+        t: Option<&models::Type>,
+        limit: u32,
+    ) -> Result<Vec<(models::EdgeDirection, models::Edge)>> {
+        let mut adjacent = Vec::new();
+
+        for direction in &[models::EdgeDirection::Outbound, models::EdgeDirection::Inbound] {
+            let remaining = limit - adjacent.len() as u32;
+
+            if remaining == 0 {
+                break;
+            }
+
+            let inner = models::SpecificVertexQuery::single(id).into();
+            let mut q = models::PipeEdgeQuery::new(Box::new(inner), *direction).limit(remaining);
+
+            if let Some(t) = t {
+                q = q.t(t.clone());
+            }
+
+            adjacent.extend(self.get_edges(q)?.into_iter().map(|edge| (*direction, edge)));
+        }
+
+        Ok(adjacent)
+    }
+
     /// Gets the number of edges associated with a vertex.
     ///
     /// # Arguments
@@ -142,6 +1214,100 @@ pub trait Transaction {
     /// * `name` - The property name.
     fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>>;
 
+    /// Finds vertices whose property value matches a predicate. Vertices
+    /// that don't have the property don't match.
+    ///
+    /// The default implementation fetches the property for every vertex
+    /// returned by the inner query and filters them, so narrow the inner
+    /// query (e.g. by type) where possible. Limits on the inner query apply
+    /// before filtering.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `predicate` - The condition the property value must meet.
+    fn find_vertices_with_property(
+        &self,
+        q: models::VertexPropertyQuery,
+        predicate: &models::PropertyPredicate,
+    ) -> Result<Vec<models::Vertex>> {
+        let ids: Vec<Uuid> = self
+            .get_vertex_properties(q)?
+            .into_iter()
+            .filter(|p| predicate.matches(&p.value))
+            .map(|p| p.id)
+            .collect();
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.get_vertices(models::SpecificVertexQuery::new(ids))
+    }
+
+    /// Adds a label to a vertex. Labels put vertices in categories beyond
+    /// their single type, and are stored in the `LABELS_PROPERTY` vertex
+    /// property as an array of strings. Returns whether the label was
+    /// added, which it isn't if the vertex doesn't exist or already has the
+    /// label.
+    ///
+    /// The default implementation reads the labels and writes them back, so
+    /// concurrent changes to a vertex's labels may overwrite each other.
+    ///
+    /// # Arguments
+    /// * `id`: The id of the vertex.
+    /// * `label`: The label to add.
+    fn add_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        if self.get_vertices(models::SpecificVertexQuery::single(id))?.is_empty() {
+            return Ok(false);
+        }
+
+        let q = models::SpecificVertexQuery::single(id).property(models::LABELS_PROPERTY);
+        let mut labels = get_labels(self, q.clone())?;
+
+        if labels.iter().any(|l| l == label) {
+            return Ok(false);
+        }
+
+        labels.push(label.to_string());
+        labels.sort();
+        self.set_vertex_properties(q, &JsonValue::from(labels))?;
+        Ok(true)
+    }
+
+    /// Removes a label from a vertex. Returns whether the label was removed,
+    /// which it isn't if the vertex doesn't have the label.
+    ///
+    /// As with `add_vertex_label`, the default implementation isn't atomic.
+    ///
+    /// # Arguments
+    /// * `id`: The id of the vertex.
+    /// * `label`: The label to remove.
+    fn remove_vertex_label(&self, id: Uuid, label: &str) -> Result<bool> {
+        let q = models::SpecificVertexQuery::single(id).property(models::LABELS_PROPERTY);
+        let mut labels = get_labels(self, q.clone())?;
+        let len = labels.len();
+        labels.retain(|l| l != label);
+
+        if labels.len() == len {
+            return Ok(false);
+        }
+
+        self.set_vertex_properties(q, &JsonValue::from(labels))?;
+        Ok(true)
+    }
+
+    /// Gets the vertices returned by a query that have a label. This is a
+    /// `find_vertices_with_property` on `LABELS_PROPERTY`, so limits on the
+    /// query apply before filtering.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label to filter by.
+    fn get_vertices_with_label<Q: Into<models::VertexQuery>>(&self, q: Q, label: &str) -> Result<Vec<models::Vertex>> {
+        let predicate = models::PropertyPredicate::Contains(JsonValue::from(label));
+        self.find_vertices_with_property(q.into().property(models::LABELS_PROPERTY), &predicate)
+    }
+
     /// Gets all vertex properties.
     ///
     /// # Arguments
@@ -156,6 +1322,37 @@ pub trait Transaction {
     /// * `value` - The property value.
     fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: &JsonValue) -> Result<()>;
 
+    /// Updates vertex properties by applying a JSON merge patch (RFC 7386)
+    /// to their current values. Vertices that don't have the property yet
+    /// have the patch applied to null.
+    ///
+    /// The default implementation is not atomic: it reads each value and
+    /// writes it back in separate calls, so a concurrent write to the same
+    /// property between the two is lost. The memory datastore overrides it
+    /// to patch under its write lock, which is atomic; the RocksDB and sled
+    /// datastores use the default, so callers that patch the same property
+    /// concurrently there need to serialize the patches themselves.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `patch` - The merge patch to apply.
+    fn patch_vertex_properties(&self, q: models::VertexPropertyQuery, patch: &JsonValue) -> Result<()> {
+        let ids: Vec<Uuid> = self.get_vertices(q.inner)?.into_iter().map(|v| v.id).collect();
+        let mut values: HashMap<Uuid, JsonValue> = self
+            .get_vertex_properties(models::SpecificVertexQuery::new(ids.clone()).property(q.name.clone()))?
+            .into_iter()
+            .map(|p| (p.id, p.value))
+            .collect();
+
+        for id in ids {
+            let value = values.entry(id).or_insert(JsonValue::Null);
+            merge_patch(value, patch);
+            self.set_vertex_properties(models::SpecificVertexQuery::single(id).property(q.name.clone()), value)?;
+        }
+
+        Ok(())
+    }
+
     /// Deletes vertex properties.
     ///
     /// # Arguments
@@ -184,6 +1381,37 @@ pub trait Transaction {
     /// * `value` - The property value.
     fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: &JsonValue) -> Result<()>;
 
+    /// Updates edge properties by applying a JSON merge patch (RFC 7386) to
+    /// their current values. Edges that don't have the property yet have the
+    /// patch applied to null.
+    ///
+    /// The default implementation is not atomic: it reads each value and
+    /// writes it back in separate calls, so a concurrent write to the same
+    /// property between the two is lost. The memory datastore overrides it
+    /// to patch under its write lock, which is atomic; the RocksDB and sled
+    /// datastores use the default, so callers that patch the same property
+    /// concurrently there need to serialize the patches themselves.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `patch` - The merge patch to apply.
+    fn patch_edge_properties(&self, q: models::EdgePropertyQuery, patch: &JsonValue) -> Result<()> {
+        let keys: Vec<models::EdgeKey> = self.get_edges(q.inner)?.into_iter().map(|e| e.key).collect();
+        let mut values: HashMap<models::EdgeKey, JsonValue> = self
+            .get_edge_properties(models::SpecificEdgeQuery::new(keys.clone()).property(q.name.clone()))?
+            .into_iter()
+            .map(|p| (p.key, p.value))
+            .collect();
+
+        for key in keys {
+            let value = values.entry(key.clone()).or_insert(JsonValue::Null);
+            merge_patch(value, patch);
+            self.set_edge_properties(models::SpecificEdgeQuery::single(key).property(q.name.clone()), value)?;
+        }
+
+        Ok(())
+    }
+
     /// Deletes edge properties.
     ///
     /// # Arguments
@@ -3,12 +3,19 @@
 use crate::errors::{ValidationError, ValidationResult};
 use chrono::offset::Utc;
 use lazy_static::lazy_static;
+use serde_json::Value as JsonValue;
 use uuid::v1::{Context, Timestamp};
 use uuid::Uuid;
 
 const TEMP_PATH_RANDOM_PART_LENGTH: usize = 8;
 const NODE_ID: [u8; 6] = [0, 0, 0, 0, 0, 0];
 
+// The namespace for UUIDs generated from external keys. This must never
+// change, or existing keys would map to different UUIDs.
+const EXTERNAL_KEY_NAMESPACE: Uuid = Uuid::from_bytes([
+    0x8f, 0x4b, 0x92, 0x85, 0x19, 0x1d, 0x4b, 0x76, 0xa4, 0x62, 0x45, 0xd9, 0xb2, 0xf5, 0x72, 0x7c,
+]);
+
 lazy_static! {
     static ref CONTEXT: Context = Context::new(0);
 }
@@ -33,6 +40,16 @@ pub fn generate_uuid_v1() -> Uuid {
     Uuid::new_v1(ts, &NODE_ID).expect("Expected to be able to generate a UUID")
 }
 
+/// Generates a UUID v5 from an external key. The same key always generates
+/// the same UUID.
+///
+/// # Arguments
+///
+/// * `key` - The external key.
+pub fn generate_uuid_v5(key: &str) -> Uuid {
+    Uuid::new_v5(&EXTERNAL_KEY_NAMESPACE, key.as_bytes())
+}
+
 /// Generates a securely random string consisting of letters (uppercase and
 /// lowercase) and digits.
 pub(crate) fn generate_random_secret(count: usize) -> String {
@@ -76,11 +93,83 @@ pub(crate) fn next_uuid(uuid: Uuid) -> ValidationResult<Uuid> {
     Err(ValidationError::CannotIncrementUuid)
 }
 
+/// Keeps a uniformly random sample of up to a fixed number of items from a
+/// stream of unknown length, via reservoir sampling.
+pub(crate) struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            items: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add<R: rand::Rng + ?Sized>(&mut self, item: T, rng: &mut R) {
+        self.seen += 1;
+
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let i = rng.gen_range(0..self.seen);
+
+            if i < self.capacity as u64 {
+                self.items[i as usize] = item;
+            }
+        }
+    }
+
+    pub(crate) fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+/// Applies a JSON merge patch, as described in RFC 7386, to a value in
+/// place. Objects in the patch are merged into the target recursively, with
+/// null members removing the corresponding member; anything else replaces
+/// the target outright.
+///
+/// # Arguments
+/// * `target` - The value to patch.
+/// * `patch` - The patch to apply.
+pub fn merge_patch(target: &mut JsonValue, patch: &JsonValue) {
+    let patch = match patch {
+        JsonValue::Object(patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = JsonValue::Object(Default::default());
+    }
+
+    let target = target.as_object_mut().unwrap();
+
+    for (name, value) in patch {
+        if value.is_null() {
+            target.remove(name);
+        } else {
+            merge_patch(target.entry(name.clone()).or_insert(JsonValue::Null), value);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{generate_random_secret, generate_temporary_path, generate_uuid_v1, next_uuid};
+    use super::{
+        generate_random_secret, generate_temporary_path, generate_uuid_v1, generate_uuid_v5, merge_patch, next_uuid,
+        Reservoir,
+    };
     use core::str::FromStr;
     use regex::Regex;
+    use serde_json::Value as JsonValue;
     use uuid::Uuid;
 
     #[test]
@@ -92,6 +181,12 @@ mod tests {
         assert_ne!(first, second);
     }
 
+    #[test]
+    fn should_generate_uuid_v5() {
+        assert_eq!(generate_uuid_v5("key"), generate_uuid_v5("key"));
+        assert_ne!(generate_uuid_v5("key"), generate_uuid_v5("other key"));
+    }
+
     #[test]
     fn should_generate_new_uuid_v1() {
         let first = generate_uuid_v1();
@@ -99,6 +194,25 @@ mod tests {
         assert_ne!(first, second);
     }
 
+    #[test]
+    fn should_sample_with_a_reservoir() {
+        let mut reservoir = Reservoir::new(3);
+
+        for i in 0..100 {
+            reservoir.add(i, &mut rand::thread_rng());
+        }
+
+        let mut items = reservoir.into_items();
+        assert_eq!(items.len(), 3);
+        items.sort_unstable();
+        items.dedup();
+        assert_eq!(items.len(), 3);
+
+        let mut reservoir = Reservoir::new(3);
+        reservoir.add(1, &mut rand::thread_rng());
+        assert_eq!(reservoir.into_items(), vec![1]);
+    }
+
     #[test]
     fn should_generate_random_secret() {
         let secret = generate_random_secret(62);
@@ -117,4 +231,36 @@ mod tests {
         let from_uuid = Uuid::from_str("ffffffff-ffff-ffff-ffff-ffffffffffff").unwrap();
         assert!(next_uuid(from_uuid).is_err());
     }
+
+    #[test]
+    fn should_merge_patch() {
+        // Test cases from RFC 7386, appendix A
+        let cases = [
+            (r#"{"a": "b"}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#),
+            (r#"{"a": "b"}"#, r#"{"b": "c"}"#, r#"{"a": "b", "b": "c"}"#),
+            (r#"{"a": "b"}"#, r#"{"a": null}"#, r#"{}"#),
+            (r#"{"a": "b", "b": "c"}"#, r#"{"a": null}"#, r#"{"b": "c"}"#),
+            (r#"{"a": ["b"]}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#),
+            (r#"{"a": "c"}"#, r#"{"a": ["b"]}"#, r#"{"a": ["b"]}"#),
+            (
+                r#"{"a": {"b": "c"}}"#,
+                r#"{"a": {"b": "d", "c": null}}"#,
+                r#"{"a": {"b": "d"}}"#,
+            ),
+            (r#"{"a": [{"b": "c"}]}"#, r#"{"a": [1]}"#, r#"{"a": [1]}"#),
+            (r#"["a", "b"]"#, r#"["c", "d"]"#, r#"["c", "d"]"#),
+            (r#"{"a": "b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a": "foo"}"#, r#"null"#, r#"null"#),
+            (r#"{"a": "foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e": null}"#, r#"{"a": 1}"#, r#"{"e": null, "a": 1}"#),
+            (r#"[1, 2]"#, r#"{"a": "b", "c": null}"#, r#"{"a": "b"}"#),
+            (r#"{}"#, r#"{"a": {"bb": {"ccc": null}}}"#, r#"{"a": {"bb": {}}}"#),
+        ];
+
+        for (target, patch, expected) in cases.iter() {
+            let mut target: JsonValue = serde_json::from_str(target).unwrap();
+            merge_patch(&mut target, &serde_json::from_str(patch).unwrap());
+            assert_eq!(target, serde_json::from_str::<JsonValue>(expected).unwrap());
+        }
+    }
 }